@@ -7,6 +7,7 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+use crate::PublicKey;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -93,6 +94,15 @@ pub enum Error {
     BalanceExists,
     /// Expected data size exceeded.
     ExceededSize,
+    /// A chunk of ImmutableData does not match the data map it is reassembled from.
+    InvalidChunk,
+    /// Received data does not match the address it was requested from.
+    ContentMismatch,
+    /// Some entries, permissions or owners were added without the required permissions. Contains
+    /// the index and author of each of them.
+    UnauthorisedEntries(BTreeMap<HistoryIndex, PublicKey>),
+    /// Permission grant for a given requester is not in effect anymore
+    PermissionExpired,
 }
 
 impl<T: Into<String>> From<T> for Error {
@@ -150,6 +160,12 @@ impl Display for Error {
             Error::BalanceExists => write!(f, "Balance already exists"),
             Error::DuplicateMessageId => write!(f, "MessageId already exists"),
             Error::ExceededSize => write!(f, "Size of the structure exceeds the limit"),
+            Error::InvalidChunk => write!(f, "Chunk does not match the data map"),
+            Error::ContentMismatch => write!(f, "Data does not match the requested address"),
+            Error::UnauthorisedEntries(ref entries) => {
+                write!(f, "Entries were added without permission: {:?}", entries)
+            }
+            Error::PermissionExpired => write!(f, "Permission grant has expired"),
         }
     }
 }
@@ -186,10 +202,25 @@ impl error::Error for Error {
             Error::BalanceExists => "Balance already exists",
             Error::DuplicateMessageId => "MessageId already exists",
             Error::ExceededSize => "Exceeded the size limit",
+            Error::InvalidChunk => "Invalid chunk",
+            Error::ContentMismatch => "Content mismatch",
+            Error::UnauthorisedEntries(_) => "Unauthorised entries",
+            Error::PermissionExpired => "Permission expired",
         }
     }
 }
 
+/// Index of an entry in the history of a Sequence, for `Error::UnauthorisedEntries`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum HistoryIndex {
+    /// Index of a data entry.
+    Entry(u64),
+    /// Index of permissions.
+    Permissions(u64),
+    /// Index of an owner.
+    Owner(u64),
+}
+
 /// Entry error for `Error::InvalidEntryActions`.
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum EntryError {
@@ -199,4 +230,6 @@ pub enum EntryError {
     EntryExists(u8),
     /// Invalid version when updating an entry. Contains the current entry Key.
     InvalidSuccessor(u8),
+    /// Precondition on an entry does not hold. Contains the current entry version.
+    PreconditionFailed(u8),
 }
@@ -0,0 +1,198 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Chunking of byte streams larger than an ImmutableData.
+//!
+//! A stream is split into chunks of at most `MAX_CHUNK_SIZE_IN_BYTES`, each stored as an
+//! ImmutableData, and a `DataMap` recording the address, size and hash of every chunk in order is
+//! itself stored as an ImmutableData. Reassembling the stream checks every chunk against the data
+//! map.
+//!
+//! Chunks can optionally be encrypted convergently: each chunk is encrypted with a keystream
+//! derived from the SHA3-256 hash of its content, which only the data map records. Identical
+//! content is thus stored as identical chunks, while the chunks alone don't reveal it.
+//!
+//! As the data map holds the keys of the chunks, encryption only protects the content from those
+//! who can't read the data map. Only unpublished chunks can thus be encrypted, their data map
+//! being unpublished data readable by its owner, and by the vaults holding it. A published data
+//! map would hand the keys to anyone.
+
+use super::{Address, Data, PubData, UnpubData};
+use crate::{utils, Error, PublicKey, Result, XorName};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Maximum size of the content of a chunk, leaving room for the serialisation overhead within
+/// `MAX_IMMUTABLE_DATA_SIZE_IN_BYTES`.
+pub const MAX_CHUNK_SIZE_IN_BYTES: u64 = 1024 * 1024;
+
+/// Details of a chunk recorded in a data map.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChunkDetails {
+    /// Address of the chunk.
+    pub address: Address,
+    /// SHA3-256 hash of the content of the chunk before encryption.
+    pub hash: XorName,
+    /// Size of the content of the chunk.
+    pub size: u64,
+}
+
+/// Map of the chunks a byte stream was split into.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DataMap {
+    /// Size of the whole stream.
+    size: u64,
+    /// Whether the chunks are encrypted.
+    encrypted: bool,
+    /// Chunks, in the order of the stream.
+    chunks: Vec<ChunkDetails>,
+}
+
+impl DataMap {
+    /// Splits `bytes` into chunks, encrypting them if `encrypt` is set. If `owner` is provided,
+    /// the chunks are unpublished data owned by it, and published data otherwise.
+    ///
+    /// Returns the data map, stored as ImmutableData of the same kind as the chunks, and the
+    /// chunks in order.
+    ///
+    /// Returns:
+    /// `Err::InvalidOperation` if `encrypt` is set without an `owner`, as the published data map
+    /// would reveal the keys of the chunks,
+    /// `Err::ExceededSize` if the data map doesn't fit in an ImmutableData.
+    pub fn split(
+        bytes: &[u8],
+        owner: Option<PublicKey>,
+        encrypt: bool,
+    ) -> Result<(Data, Vec<Data>)> {
+        if encrypt && owner.is_none() {
+            return Err(Error::InvalidOperation);
+        }
+
+        let (chunks, details) = bytes
+            .chunks(MAX_CHUNK_SIZE_IN_BYTES as usize)
+            .map(|content| {
+                let hash = XorName(tiny_keccak::sha3_256(content));
+                let value = if encrypt {
+                    apply_keystream(content, &hash)
+                } else {
+                    content.to_vec()
+                };
+                let chunk = to_data(value, owner);
+                let details = ChunkDetails {
+                    address: *chunk.address(),
+                    hash,
+                    size: content.len() as u64,
+                };
+                (chunk, details)
+            })
+            .unzip();
+        let data_map = DataMap {
+            size: bytes.len() as u64,
+            encrypted: encrypt,
+            chunks: details,
+        };
+
+        let data_map = to_data(utils::serialise(&data_map), owner);
+        if !data_map.validate_size() {
+            return Err(Error::ExceededSize);
+        }
+        Ok((data_map, chunks))
+    }
+
+    /// Parses the data map stored in `data`.
+    ///
+    /// Returns `Err(Error::FailedToParse(_))` if `data` doesn't hold a data map, or if the size of
+    /// the stream doesn't match the sizes of its chunks.
+    pub fn from_data(data: &Data) -> Result<Self> {
+        let data_map: Self = utils::deserialise(data.value())?;
+        let chunks_size = data_map
+            .chunks
+            .iter()
+            .try_fold(0u64, |total, details| total.checked_add(details.size));
+        if chunks_size != Some(data_map.size) {
+            return Err(Error::FailedToParse(
+                "Data map size doesn't match its chunks".to_string(),
+            ));
+        }
+        Ok(data_map)
+    }
+
+    /// Returns the size of the whole stream.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns `true` if the chunks are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Returns the details of the chunks, in the order of the stream.
+    pub fn chunks(&self) -> &[ChunkDetails] {
+        &self.chunks
+    }
+
+    /// Reassembles the stream from its `chunks`, given in any order.
+    ///
+    /// Returns:
+    /// `Err::NoSuchData` if a chunk of the data map is missing,
+    /// `Err::InvalidChunk` if the content of a chunk doesn't match its hash in the data map.
+    pub fn reassemble<'a>(&self, chunks: impl IntoIterator<Item = &'a Data>) -> Result<Vec<u8>> {
+        let chunks: BTreeMap<_, _> = chunks
+            .into_iter()
+            .map(|chunk| (*chunk.address(), chunk))
+            .collect();
+
+        // The size isn't trusted for preallocating, as it may come from a malformed data map.
+        let mut bytes = Vec::new();
+        for details in &self.chunks {
+            let chunk = chunks.get(&details.address).ok_or(Error::NoSuchData)?;
+            let content = if self.encrypted {
+                apply_keystream(chunk.value(), &details.hash)
+            } else {
+                chunk.value().clone()
+            };
+            if content.len() as u64 != details.size
+                || XorName(tiny_keccak::sha3_256(&content)) != details.hash
+            {
+                return Err(Error::InvalidChunk);
+            }
+            bytes.extend(content);
+        }
+
+        if bytes.len() as u64 != self.size {
+            return Err(Error::InvalidChunk);
+        }
+        Ok(bytes)
+    }
+}
+
+fn to_data(value: Vec<u8>, owner: Option<PublicKey>) -> Data {
+    match owner {
+        Some(owner) => UnpubData::new(value, owner).into(),
+        None => PubData::new(value).into(),
+    }
+}
+
+// Encrypts or decrypts `bytes` by XORing them with a keystream made of the SHA3-256 hashes of
+// `key` followed by the index of each 32-byte block.
+pub(super) fn apply_keystream(bytes: &[u8], key: &XorName) -> Vec<u8> {
+    bytes
+        .chunks(32)
+        .enumerate()
+        .flat_map(|(index, block)| {
+            let keystream = tiny_keccak::sha3_256(&utils::serialise(&(key, index as u64)));
+            block
+                .iter()
+                .zip(keystream.iter())
+                .map(|(byte, key_byte)| byte ^ key_byte)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
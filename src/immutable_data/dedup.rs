@@ -0,0 +1,164 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Deduplicated storage of unpublished ImmutableData.
+//!
+//! The address of an `UnpubData` includes its owner, so identical content stored by different
+//! owners lives at different addresses. A `DedupStore` keeps a single copy of the content, as a
+//! payload encrypted convergently with the hash of the content and addressed by the hash of the
+//! encrypted payload, and a reference per owner. The payload is reference counted: storing a copy
+//! adds a reference to it, deleting a copy removes its reference only, and the payload is dropped
+//! along with its last reference.
+//!
+//! Payloads are encrypted at rest: the store doesn't keep their key, the SHA3-256 hash of the
+//! content, which the owners hold and must provide to read a copy back. The content is only in
+//! the clear while it is being stored with `put` or read back with `get`.
+//!
+//! A vault holding unpublished data in a `DedupStore` handles an `IDataRequest::Put` of
+//! unpublished data with `put` and an `IDataRequest::DeleteUnpub` with `delete`, which adjust the
+//! reference counts. It answers an `IDataRequest::GetUnpub` with `get`, given the key carried by
+//! the request, and an `IDataRequest::GetUnpubRange` with a slice of the data `get` returns. As
+//! `IDataRequest::Get` and `IDataRequest::GetRange` don't carry a key, they can't be answered for
+//! unpublished data held in a `DedupStore`.
+
+use super::{chunks::apply_keystream, Address, Encoding, UnpubData};
+use crate::{Error, PublicKey, Result, XorName};
+use serde::{Deserialize, Serialize};
+use std::collections::{btree_map::Entry, BTreeMap};
+
+/// Reference of an owner to the shared payload holding the content of its unpublished data.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Reference {
+    /// Owner of the data.
+    pub owner: PublicKey,
+    /// Name of the shared payload, the SHA3-256 hash of the encrypted content.
+    pub payload: XorName,
+    /// Encoding the data was stored with.
+    pub encoding: Encoding,
+}
+
+// Encrypted content shared by all the owners of the same data.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SharedPayload {
+    value: Vec<u8>,
+    ref_count: u64,
+}
+
+/// Storage of unpublished ImmutableData, deduplicating identical content across owners.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DedupStore {
+    /// Shared payloads, keyed by name.
+    payloads: BTreeMap<XorName, SharedPayload>,
+    /// References of the owners, keyed by the name of their data.
+    references: BTreeMap<XorName, Reference>,
+}
+
+impl DedupStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Stores a copy of `data`, as handled for an `IDataRequest::Put` of unpublished data. The
+    /// shared payload is only stored if no other owner holds the same content.
+    ///
+    /// Returns `Err(Error::DataExists)` if the data is already stored.
+    pub fn put(&mut self, data: &UnpubData) -> Result<()> {
+        let entry = match self.references.entry(*data.name()) {
+            Entry::Occupied(_) => return Err(Error::DataExists),
+            Entry::Vacant(entry) => entry,
+        };
+
+        let key = XorName(tiny_keccak::sha3_256(data.value()));
+        let value = apply_keystream(data.value(), &key);
+        let payload = XorName(tiny_keccak::sha3_256(&value));
+        self.payloads
+            .entry(payload)
+            .or_insert(SharedPayload {
+                value,
+                ref_count: 0,
+            })
+            .ref_count += 1;
+        let _ = entry.insert(Reference {
+            owner: *data.owner(),
+            payload,
+            encoding: data.encoding(),
+        });
+
+        Ok(())
+    }
+
+    /// Gets the data stored at `address`, decrypting its payload with `key`, the SHA3-256 hash of
+    /// its content. The data is returned with the encoding it was stored with.
+    ///
+    /// Returns:
+    /// `Err::NoSuchData` if no data is stored at `address`,
+    /// `Err::ContentMismatch` if `key` is not the key of the data.
+    pub fn get(&self, address: &Address, key: &XorName) -> Result<UnpubData> {
+        let reference = self.reference(address).ok_or(Error::NoSuchData)?;
+        let payload = self
+            .payloads
+            .get(&reference.payload)
+            .ok_or(Error::NoSuchData)?;
+        let value = apply_keystream(&payload.value, key);
+        if XorName(tiny_keccak::sha3_256(&value)) != *key {
+            return Err(Error::ContentMismatch);
+        }
+
+        Ok(UnpubData::new_with_encoding(
+            value,
+            reference.owner,
+            reference.encoding,
+        ))
+    }
+
+    /// Deletes the copy of the data stored at `address`, as handled for an
+    /// `IDataRequest::DeleteUnpub`. The copies of the other owners are left untouched, and the
+    /// shared payload is only deleted along with its last reference.
+    ///
+    /// Returns:
+    /// `Err::NoSuchData` if no data is stored at `address`,
+    /// `Err::AccessDenied` if `requester` is not the owner of the data.
+    pub fn delete(&mut self, address: &Address, requester: PublicKey) -> Result<()> {
+        let reference = *self.reference(address).ok_or(Error::NoSuchData)?;
+        if reference.owner != requester {
+            return Err(Error::AccessDenied);
+        }
+
+        let _ = self.references.remove(address.name());
+        if let Entry::Occupied(mut entry) = self.payloads.entry(reference.payload) {
+            entry.get_mut().ref_count -= 1;
+            if entry.get().ref_count == 0 {
+                let _ = entry.remove();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the reference of the data stored at `address`, if any.
+    pub fn reference(&self, address: &Address) -> Option<&Reference> {
+        match address {
+            Address::Unpub(name) => self.references.get(name),
+            Address::Pub(_) => None,
+        }
+    }
+
+    /// Returns the number of references to the shared payload with the given name.
+    pub fn ref_count(&self, payload: &XorName) -> u64 {
+        self.payloads
+            .get(payload)
+            .map_or(0, |payload| payload.ref_count)
+    }
+
+    /// Returns the number of shared payloads stored.
+    pub fn payload_count(&self) -> usize {
+        self.payloads.len()
+    }
+}
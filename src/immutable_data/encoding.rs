@@ -0,0 +1,55 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Encodings of ImmutableData values when serialised.
+
+use crate::{Error, Result};
+use miniz_oxide::{deflate, inflate};
+use serde::{Deserialize, Serialize};
+
+/// Maximum allowed size of the value of an ImmutableData once decompressed.
+pub const MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Compression level used for the deflate encoding, trading speed for size as zlib's default.
+const DEFLATE_LEVEL: u8 = 6;
+
+/// Encoding of the value of an ImmutableData when serialised.
+///
+/// The address of the data is always computed over the decoded value, so it doesn't depend on
+/// the encoding.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Encoding {
+    /// Stored as is.
+    Raw,
+    /// Compressed with raw deflate.
+    Deflate,
+}
+
+impl Encoding {
+    // Encodes `value`, returning `None` for the raw encoding.
+    pub(super) fn encode(self, value: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Encoding::Raw => None,
+            Encoding::Deflate => Some(deflate::compress_to_vec(value, DEFLATE_LEVEL)),
+        }
+    }
+
+    // Decodes `encoded` into the value, failing with `Error::FailedToParse` if it is not valid or
+    // decodes to more than `MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES`.
+    pub(super) fn decode(self, encoded: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Raw => Ok(encoded.to_vec()),
+            Encoding::Deflate => inflate::decompress_to_vec_with_limit(
+                encoded,
+                MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES as usize,
+            )
+            .map_err(|error| Error::FailedToParse(error.to_string())),
+        }
+    }
+}
@@ -0,0 +1,878 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+mod chunks;
+mod dedup;
+mod encoding;
+
+pub use chunks::{ChunkDetails, DataMap, MAX_CHUNK_SIZE_IN_BYTES};
+pub use dedup::{DedupStore, Reference};
+pub use encoding::{Encoding, MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES};
+
+use crate::{utils, Error, PublicKey, XorName};
+use bincode::serialized_size;
+use multibase::Decodable;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    cmp::{self, Ordering},
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    u64,
+};
+
+/// Maximum allowed size for a serialised ImmutableData to grow to.
+pub const MAX_IMMUTABLE_DATA_SIZE_IN_BYTES: u64 = 1024 * 1024 + 10 * 1024;
+
+/// Unpublished ImmutableData: an immutable chunk of data which can be deleted. Can only be fetched
+/// by the listed owner.
+///
+/// The encoding of the value is only kept when serialised as part of a `Data`, and doesn't take
+/// part in comparisons: on its own, the data is always serialised with its value raw.
+#[derive(Clone)]
+pub struct UnpubData {
+    /// Network address. Omitted when serialising and calculated from the `value` and `owner` when
+    /// deserialising.
+    address: Address,
+    /// Contained data.
+    value: Vec<u8>,
+    /// Contains a set of owners of this data. DataManagers enforce that a DELETE or OWNED-GET type
+    /// of request is coming from the MaidManager Authority of the owners.
+    owner: PublicKey,
+    /// Encoding of the value when serialising as part of a `Data`.
+    encoding: Encoding,
+    /// Value as serialised as part of a `Data`, if not raw.
+    encoded: Option<Vec<u8>>,
+}
+
+impl UnpubData {
+    /// Creates a new instance of `UnpubData`.
+    pub fn new(value: Vec<u8>, owner: PublicKey) -> Self {
+        Self::new_with_encoding(value, owner, Encoding::Raw)
+    }
+
+    /// Creates a new instance of `UnpubData` whose value is serialised with `encoding`.
+    pub fn new_with_encoding(value: Vec<u8>, owner: PublicKey, encoding: Encoding) -> Self {
+        let encoded = encoding.encode(&value);
+        Self::from_parts(value, owner, encoding, encoded)
+    }
+
+    fn from_parts(
+        value: Vec<u8>,
+        owner: PublicKey,
+        encoding: Encoding,
+        encoded: Option<Vec<u8>>,
+    ) -> Self {
+        let hash_of_value = tiny_keccak::sha3_256(&value);
+        let serialised_contents = utils::serialise(&(hash_of_value, &owner));
+        let address = Address::Unpub(XorName(tiny_keccak::sha3_256(&serialised_contents)));
+
+        Self {
+            address,
+            value,
+            owner,
+            encoding,
+            encoded,
+        }
+    }
+
+    /// Returns the value, decoded.
+    pub fn value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    /// Returns the encoding of the value when serialising as part of a `Data`.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the set of owners.
+    pub fn owner(&self) -> &PublicKey {
+        &self.owner
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+
+    /// Returns size of contained value.
+    pub fn payload_size(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Returns size of this data after serialisation, with the value encoded.
+    pub fn serialised_size(&self) -> u64 {
+        match &self.encoded {
+            Some(encoded) => serialized_size(&(self.encoding, encoded, &self.owner)),
+            None => serialized_size(self),
+        }
+        .unwrap_or(u64::MAX)
+    }
+
+    /// Returns `true` if the size, with the value encoded, is valid.
+    pub fn validate_size(&self) -> bool {
+        self.value.len() as u64 <= MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES
+            && self.serialised_size() <= MAX_IMMUTABLE_DATA_SIZE_IN_BYTES
+    }
+
+    fn compared_parts(&self) -> (&Address, &Vec<u8>, &PublicKey) {
+        (&self.address, &self.value, &self.owner)
+    }
+}
+
+impl Serialize for UnpubData {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        (&self.value, &self.owner).serialize(serialiser)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnpubData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, owner): (Vec<u8>, PublicKey) = Deserialize::deserialize(deserializer)?;
+        Ok(UnpubData::new(value, owner))
+    }
+}
+
+impl PartialEq for UnpubData {
+    fn eq(&self, other: &Self) -> bool {
+        self.compared_parts() == other.compared_parts()
+    }
+}
+
+impl Eq for UnpubData {}
+
+impl PartialOrd for UnpubData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UnpubData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compared_parts().cmp(&other.compared_parts())
+    }
+}
+
+impl Hash for UnpubData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.compared_parts().hash(state)
+    }
+}
+
+impl Debug for UnpubData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        // TODO: Output owners?
+        write!(formatter, "UnpubImmutableData {:?}", self.name())
+    }
+}
+
+/// Published ImmutableData: an immutable chunk of data which cannot be deleted.
+///
+/// The encoding of the value is only kept when serialised as part of a `Data`, and doesn't take
+/// part in comparisons: on its own, the data is always serialised with its value raw.
+#[derive(Clone)]
+pub struct PubData {
+    /// Network address. Omitted when serialising and calculated from the `value` when
+    /// deserialising.
+    address: Address,
+    /// Contained data.
+    value: Vec<u8>,
+    /// Encoding of the value when serialising as part of a `Data`.
+    encoding: Encoding,
+    /// Value as serialised as part of a `Data`, if not raw.
+    encoded: Option<Vec<u8>>,
+}
+
+impl PubData {
+    /// Creates a new instance of `ImmutableData`.
+    pub fn new(value: Vec<u8>) -> Self {
+        Self::new_with_encoding(value, Encoding::Raw)
+    }
+
+    /// Creates a new instance of `ImmutableData` whose value is serialised with `encoding`.
+    pub fn new_with_encoding(value: Vec<u8>, encoding: Encoding) -> Self {
+        let encoded = encoding.encode(&value);
+        Self::from_parts(value, encoding, encoded)
+    }
+
+    fn from_parts(value: Vec<u8>, encoding: Encoding, encoded: Option<Vec<u8>>) -> Self {
+        Self {
+            address: Address::Pub(XorName(tiny_keccak::sha3_256(&value))),
+            value,
+            encoding,
+            encoded,
+        }
+    }
+
+    /// Returns the value, decoded.
+    pub fn value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    /// Returns the encoding of the value when serialising as part of a `Data`.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+
+    /// Returns size of contained value.
+    pub fn payload_size(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Returns size of this data after serialisation, with the value encoded.
+    pub fn serialised_size(&self) -> u64 {
+        match &self.encoded {
+            Some(encoded) => serialized_size(&(self.encoding, encoded)),
+            None => serialized_size(self),
+        }
+        .unwrap_or(u64::MAX)
+    }
+
+    /// Returns true if the size, with the value encoded, is valid.
+    pub fn validate_size(&self) -> bool {
+        self.value.len() as u64 <= MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES
+            && self.serialised_size() <= MAX_IMMUTABLE_DATA_SIZE_IN_BYTES
+    }
+
+    fn compared_parts(&self) -> (&Address, &Vec<u8>) {
+        (&self.address, &self.value)
+    }
+}
+
+impl Serialize for PubData {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serialiser)
+    }
+}
+
+impl<'de> Deserialize<'de> for PubData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: Vec<u8> = Deserialize::deserialize(deserializer)?;
+        Ok(PubData::new(value))
+    }
+}
+
+impl PartialEq for PubData {
+    fn eq(&self, other: &Self) -> bool {
+        self.compared_parts() == other.compared_parts()
+    }
+}
+
+impl Eq for PubData {}
+
+impl PartialOrd for PubData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PubData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compared_parts().cmp(&other.compared_parts())
+    }
+}
+
+impl Hash for PubData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.compared_parts().hash(state)
+    }
+}
+
+impl Debug for PubData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "PubImmutableData {:?}", self.name())
+    }
+}
+
+/// Kind of an ImmutableData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Kind {
+    /// Unpublished.
+    Unpub,
+    /// Published.
+    Pub,
+}
+
+impl Kind {
+    /// Creates `Kind` from a `published` flag.
+    pub fn from_flag(published: bool) -> Self {
+        if published {
+            Kind::Pub
+        } else {
+            Kind::Unpub
+        }
+    }
+
+    /// Returns true if published.
+    pub fn is_pub(self) -> bool {
+        self == Kind::Pub
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_unpub(self) -> bool {
+        !self.is_pub()
+    }
+}
+
+/// Address of an ImmutableData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Address {
+    /// Unpublished namespace.
+    Unpub(XorName),
+    /// Published namespace.
+    Pub(XorName),
+}
+
+impl Address {
+    /// Constructs an `Address` given `kind` and `name`.
+    pub fn from_kind(kind: Kind, name: XorName) -> Self {
+        match kind {
+            Kind::Pub => Address::Pub(name),
+            Kind::Unpub => Address::Unpub(name),
+        }
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Address::Unpub(_) => Kind::Unpub,
+            Address::Pub(_) => Kind::Pub,
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Address::Unpub(ref name) | Address::Pub(ref name) => name,
+        }
+    }
+
+    /// Returns true if published.
+    pub fn is_pub(&self) -> bool {
+        self.kind().is_pub()
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_unpub(&self) -> bool {
+        self.kind().is_unpub()
+    }
+
+    /// Returns the Address serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> String {
+        utils::encode(&self)
+    }
+
+    /// Creates from z-base-32 encoded string.
+    pub fn decode_from_zbase32<T: Decodable>(encoded: T) -> Result<Self, Error> {
+        utils::decode(encoded)
+    }
+}
+
+/// Object storing an ImmutableData variant.
+///
+/// Data whose value is raw is serialised as the baseline variants, and data whose value is
+/// encoded as further variants carrying the encoded value, so the wire format of the raw data
+/// doesn't depend on the support for encodings.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Data {
+    /// Unpublished ImmutableData.
+    Unpub(UnpubData),
+    /// Published ImmutableData.
+    Pub(PubData),
+}
+
+impl Data {
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        match self {
+            Data::Unpub(data) => data.address(),
+            Data::Pub(data) => data.address(),
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address().name()
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        self.address().kind()
+    }
+
+    /// Returns true if published.
+    pub fn is_pub(&self) -> bool {
+        self.kind().is_pub()
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_unpub(&self) -> bool {
+        self.kind().is_unpub()
+    }
+
+    /// Verifies that the data is the one stored at `address`. As the address of the data is
+    /// always computed from its content, this rejects any substituted content.
+    ///
+    /// Returns `Err(Error::ContentMismatch)` if the data doesn't match `address`.
+    pub fn verify_against(&self, address: &Address) -> Result<(), Error> {
+        if self.address() == address {
+            Ok(())
+        } else {
+            Err(Error::ContentMismatch)
+        }
+    }
+
+    /// Returns the value, decoded.
+    pub fn value(&self) -> &Vec<u8> {
+        match self {
+            Data::Unpub(data) => data.value(),
+            Data::Pub(data) => data.value(),
+        }
+    }
+
+    /// Returns the encoding of the value when serialising.
+    pub fn encoding(&self) -> Encoding {
+        match self {
+            Data::Unpub(data) => data.encoding(),
+            Data::Pub(data) => data.encoding(),
+        }
+    }
+
+    /// Returns the `len` bytes of the value starting at `offset`, without copying them. The
+    /// range is truncated to the end of the value, so it is empty if `offset` is past the end.
+    pub fn slice(&self, offset: u64, len: u64) -> &[u8] {
+        let value = self.value();
+        let start = cmp::min(offset, value.len() as u64) as usize;
+        let end = cmp::min(offset.saturating_add(len), value.len() as u64) as usize;
+        &value[start..end]
+    }
+
+    /// Returns `true` if the size is valid.
+    pub fn validate_size(&self) -> bool {
+        match self {
+            Data::Unpub(data) => data.validate_size(),
+            Data::Pub(data) => data.validate_size(),
+        }
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        match self {
+            Data::Unpub(data) => data.serialised_size(),
+            Data::Pub(data) => data.serialised_size(),
+        }
+    }
+}
+
+// Serialised form of a `Data`, the variants of the encoded data following the baseline ones.
+#[derive(Serialize, Deserialize)]
+enum SerialisedData<'a> {
+    Unpub(Cow<'a, UnpubData>),
+    Pub(Cow<'a, PubData>),
+    EncodedUnpub(Encoding, Cow<'a, [u8]>, PublicKey),
+    EncodedPub(Encoding, Cow<'a, [u8]>),
+}
+
+impl Serialize for Data {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        let serialised = match self {
+            Data::Unpub(data) => match &data.encoded {
+                Some(encoded) => {
+                    SerialisedData::EncodedUnpub(data.encoding, Cow::from(&encoded[..]), data.owner)
+                }
+                None => SerialisedData::Unpub(Cow::Borrowed(data)),
+            },
+            Data::Pub(data) => match &data.encoded {
+                Some(encoded) => SerialisedData::EncodedPub(data.encoding, Cow::from(&encoded[..])),
+                None => SerialisedData::Pub(Cow::Borrowed(data)),
+            },
+        };
+        serialised.serialize(serialiser)
+    }
+}
+
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Deserialize::deserialize(deserializer)? {
+            SerialisedData::Unpub(data) => Data::Unpub(data.into_owned()),
+            SerialisedData::Pub(data) => Data::Pub(data.into_owned()),
+            SerialisedData::EncodedUnpub(encoding, encoded, owner) => {
+                let (value, encoded) = decode(encoding, encoded.into_owned())?;
+                UnpubData::from_parts(value, owner, encoding, encoded).into()
+            }
+            SerialisedData::EncodedPub(encoding, encoded) => {
+                let (value, encoded) = decode(encoding, encoded.into_owned())?;
+                PubData::from_parts(value, encoding, encoded).into()
+            }
+        })
+    }
+}
+
+// Decodes the value serialised with `encoding`, keeping the encoded form if it is not raw.
+fn decode<E: de::Error>(
+    encoding: Encoding,
+    encoded: Vec<u8>,
+) -> Result<(Vec<u8>, Option<Vec<u8>>), E> {
+    match encoding {
+        Encoding::Raw => Ok((encoded, None)),
+        Encoding::Deflate => {
+            let value = encoding.decode(&encoded).map_err(E::custom)?;
+            Ok((value, Some(encoded)))
+        }
+    }
+}
+
+impl From<UnpubData> for Data {
+    fn from(data: UnpubData) -> Self {
+        Data::Unpub(data)
+    }
+}
+
+impl From<PubData> for Data {
+    fn from(data: PubData) -> Self {
+        Data::Pub(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        utils, Address, Data, DataMap, DedupStore, Encoding, PubData, PublicKey, UnpubData,
+        XorName, MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
+    };
+    use crate::{Error, IDataRequest, RequestType, Response};
+    use bincode::deserialize as deserialise;
+    use hex::encode;
+    use rand::{self, Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use std::{env, iter, thread};
+    use threshold_crypto::SecretKey;
+    use unwrap::unwrap;
+
+    #[test]
+    fn deterministic_name() {
+        let data1 = b"Hello".to_vec();
+        let data2 = b"Goodbye".to_vec();
+
+        let owner1 = PublicKey::Bls(SecretKey::random().public_key());
+        let owner2 = PublicKey::Bls(SecretKey::random().public_key());
+
+        let idata1 = UnpubData::new(data1.clone(), owner1);
+        let idata2 = UnpubData::new(data1, owner2);
+        let idata3 = UnpubData::new(data2.clone(), owner1);
+        let idata3_clone = UnpubData::new(data2, owner1);
+
+        assert_eq!(idata3, idata3_clone);
+
+        assert_ne!(idata1.name(), idata2.name());
+        assert_ne!(idata1.name(), idata3.name());
+        assert_ne!(idata2.name(), idata3.name());
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let value = "immutable data value".to_owned().into_bytes();
+        let immutable_data = PubData::new(value);
+        let immutable_data_name = encode(immutable_data.name().0.as_ref());
+        let expected_name = "fac2869677ee06277633c37ac7e8e5c655f3d652f707c7a79fab930d584a3016";
+
+        assert_eq!(&expected_name, &immutable_data_name);
+    }
+
+    #[test]
+    fn serialisation() {
+        let mut rng = get_rng();
+        let len = rng.gen_range(1, 10_000);
+        let value = iter::repeat_with(|| rng.gen()).take(len).collect();
+        let immutable_data = PubData::new(value);
+        let serialised = utils::serialise(&immutable_data);
+        let parsed = unwrap!(deserialise(&serialised));
+        assert_eq!(immutable_data, parsed);
+    }
+
+    fn get_rng() -> XorShiftRng {
+        let env_var_name = "RANDOM_SEED";
+        let seed = env::var(env_var_name)
+            .ok()
+            .map(|value| {
+                unwrap!(
+                    value.parse::<u64>(),
+                    "Env var 'RANDOM_SEED={}' is not a valid u64.",
+                    value
+                )
+            })
+            .unwrap_or_else(rand::random);
+        println!(
+            "To replay this '{}', set env var {}={}",
+            unwrap!(thread::current().name()),
+            env_var_name,
+            seed
+        );
+        XorShiftRng::seed_from_u64(seed)
+    }
+
+    #[test]
+    fn zbase32_encode_decode_idata_address() {
+        let name = XorName(rand::random());
+        let address = Address::Pub(name);
+        let encoded = address.encode_to_zbase32();
+        let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
+        assert_eq!(address, decoded);
+    }
+
+    #[test]
+    fn chunking() {
+        let mut rng = get_rng();
+        let len = rng.gen_range(2_100_000, 3_000_000);
+        let value: Vec<u8> = iter::repeat_with(|| rng.gen()).take(len).collect();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        for &(owner, encrypt) in &[(None, false), (Some(owner), true)] {
+            let (data_map, chunks) = unwrap!(DataMap::split(&value, owner, encrypt));
+            assert_eq!(chunks.len(), 3);
+            assert!(chunks.iter().all(Data::validate_size));
+            assert_eq!(data_map.is_pub(), owner.is_none());
+            assert_eq!(
+                chunks[0].value() == &value[..chunks[0].value().len()],
+                !encrypt
+            );
+
+            let data_map = unwrap!(DataMap::from_data(&data_map));
+            assert_eq!(data_map.size(), len as u64);
+            assert_eq!(unwrap!(data_map.reassemble(chunks.iter().rev())), value);
+
+            // missing or substituted chunks are detected
+            assert_eq!(data_map.reassemble(&chunks[1..]), Err(Error::NoSuchData));
+            let mut tampered = chunks[0].value().clone();
+            tampered[0] ^= 1;
+            let tampered: Data = match owner {
+                Some(owner) => UnpubData::new(tampered, owner).into(),
+                None => PubData::new(tampered).into(),
+            };
+            let mut details = data_map.chunks().to_vec();
+            details[0].address = *tampered.address();
+            let data_map: DataMap = unwrap!(deserialise(&utils::serialise(&(
+                len as u64, encrypt, details
+            ))));
+            assert_eq!(
+                data_map.reassemble(iter::once(&tampered).chain(&chunks[1..])),
+                Err(Error::InvalidChunk)
+            );
+        }
+
+        assert_eq!(
+            DataMap::split(&value, None, true),
+            Err(Error::InvalidOperation)
+        );
+
+        let (data_map, chunks) = unwrap!(DataMap::split(&[], None, false));
+        assert!(chunks.is_empty());
+        let data_map = unwrap!(DataMap::from_data(&data_map));
+        assert_eq!(unwrap!(data_map.reassemble(&chunks)), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn malformed_data_map() {
+        let value = b"value".to_vec();
+        let (data_map, chunks) = unwrap!(DataMap::split(&value, None, false));
+        let details = unwrap!(DataMap::from_data(&data_map)).chunks().to_vec();
+
+        for &size in &[0, value.len() as u64 + 1, u64::MAX] {
+            let data_map: Data = PubData::new(utils::serialise(&(size, false, &details))).into();
+            match DataMap::from_data(&data_map) {
+                Err(Error::FailedToParse(_)) => (),
+                result => panic!("Unexpected result: {:?}", result),
+            }
+
+            // a malformed data map used as is still fails to reassemble cleanly
+            let data_map: DataMap = unwrap!(deserialise(data_map.value()));
+            assert_eq!(data_map.reassemble(&chunks), Err(Error::InvalidChunk));
+        }
+    }
+
+    #[test]
+    fn verify_against() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let data: Data = UnpubData::new(b"value".to_vec(), owner).into();
+        assert!(data.verify_against(data.address()).is_ok());
+
+        // same content under another owner or kind, and substituted content
+        let other_owner = PublicKey::Bls(SecretKey::random().public_key());
+        let others: Vec<Data> = vec![
+            UnpubData::new(b"value".to_vec(), other_owner).into(),
+            PubData::new(b"value".to_vec()).into(),
+            UnpubData::new(b"other".to_vec(), owner).into(),
+        ];
+        for other in others {
+            assert_eq!(
+                other.verify_against(data.address()),
+                Err(Error::ContentMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn slice() {
+        let data: Data = PubData::new(b"immutable data value".to_vec()).into();
+        assert_eq!(data.slice(0, 9), b"immutable");
+        assert_eq!(data.slice(15, 100), b"value");
+        assert_eq!(data.slice(15, u64::MAX), b"value");
+        assert!(data.slice(100, 5).is_empty());
+
+        let request = IDataRequest::GetRange {
+            address: *data.address(),
+            offset: 0,
+            len: 9,
+        };
+        assert_eq!(request.get_type(), RequestType::PublicGet);
+        assert_eq!(
+            request.error_response(Error::NoSuchData),
+            Response::GetIDataRange(Err(Error::NoSuchData))
+        );
+    }
+
+    #[test]
+    fn dedup_store() {
+        let owner1 = PublicKey::Bls(SecretKey::random().public_key());
+        let owner2 = PublicKey::Bls(SecretKey::random().public_key());
+        let value = b"shared value".to_vec();
+        let key = XorName(tiny_keccak::sha3_256(&value));
+        let data1 = UnpubData::new(value.clone(), owner1);
+        let data2 = UnpubData::new_with_encoding(value, owner2, Encoding::Deflate);
+        let mut store = DedupStore::new();
+
+        unwrap!(store.put(&data1));
+        unwrap!(store.put(&data2));
+        assert_eq!(store.put(&data1), Err(Error::DataExists));
+        assert_eq!(store.payload_count(), 1);
+        let payload = unwrap!(store.reference(data1.address())).payload;
+        assert_eq!(unwrap!(store.reference(data2.address())).payload, payload);
+        assert_eq!(store.ref_count(&payload), 2);
+        let request = IDataRequest::GetUnpub {
+            address: *data2.address(),
+            key,
+        };
+        assert_eq!(request.get_type(), RequestType::PrivateGet);
+        assert_eq!(
+            request.error_response(Error::NoSuchData),
+            Response::GetIData(Err(Error::NoSuchData))
+        );
+        let stored = unwrap!(store.get(data2.address(), &key));
+        assert_eq!(stored, data2);
+        assert_eq!(stored.encoding(), Encoding::Deflate);
+
+        // the store can't read the payload back without the key
+        assert_eq!(
+            store.get(data2.address(), &XorName(rand::random())),
+            Err(Error::ContentMismatch)
+        );
+
+        // deleting one owner's copy leaves the other's intact
+        assert_eq!(
+            store.delete(data1.address(), owner2),
+            Err(Error::AccessDenied)
+        );
+        unwrap!(store.delete(data1.address(), owner1));
+        assert_eq!(store.get(data1.address(), &key), Err(Error::NoSuchData));
+        assert_eq!(unwrap!(store.get(data2.address(), &key)), data2);
+        assert_eq!(store.ref_count(&payload), 1);
+
+        unwrap!(store.delete(data2.address(), owner2));
+        assert_eq!(store.ref_count(&payload), 0);
+        assert_eq!(store.payload_count(), 0);
+    }
+
+    #[test]
+    fn compressed_payload() {
+        let value: Vec<u8> = b"text heavy document "
+            .iter()
+            .cycle()
+            .take(2 * MAX_IMMUTABLE_DATA_SIZE_IN_BYTES as usize)
+            .cloned()
+            .collect();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        let raw: Vec<Data> = vec![
+            PubData::new(value.clone()).into(),
+            UnpubData::new(value.clone(), owner).into(),
+        ];
+        let compressed: Vec<Data> = vec![
+            PubData::new_with_encoding(value.clone(), Encoding::Deflate).into(),
+            UnpubData::new_with_encoding(value.clone(), owner, Encoding::Deflate).into(),
+        ];
+        for (raw, compressed) in raw.iter().zip(&compressed) {
+            // the limit applies to the compressed form, the address to the uncompressed value
+            assert!(!raw.validate_size());
+            assert!(compressed.validate_size());
+            assert_eq!(compressed.address(), raw.address());
+            assert_eq!(compressed.encoding(), Encoding::Deflate);
+            assert_eq!(compressed.value(), &value);
+
+            let serialised = utils::serialise(compressed);
+            assert!((serialised.len() as u64) < MAX_IMMUTABLE_DATA_SIZE_IN_BYTES);
+            let parsed: Data = unwrap!(deserialise(&serialised));
+            assert_eq!(parsed.encoding(), Encoding::Deflate);
+            assert_eq!(parsed.value(), &value);
+
+            // the same content compares equal whatever its encoding
+            assert_eq!(&parsed, raw);
+        }
+
+        // corrupted compressed payloads fail to parse
+        let serialised = utils::serialise(&(3u32, Encoding::Deflate, vec![0xffu8; 16]));
+        assert!(deserialise::<Data>(&serialised).is_err());
+    }
+
+    #[test]
+    fn baseline_wire_format() {
+        let value = b"value".to_vec();
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+
+        // raw data keeps the baseline wire format, standalone and as part of a `Data`
+        let pub_data = PubData::new(value.clone());
+        let unpub_data = UnpubData::new(value.clone(), owner);
+        assert_eq!(utils::serialise(&pub_data), utils::serialise(&value));
+        assert_eq!(
+            utils::serialise(&unpub_data),
+            utils::serialise(&(&value, owner))
+        );
+        assert_eq!(
+            utils::serialise(&Data::from(unpub_data.clone())),
+            utils::serialise(&(0u32, &value, owner))
+        );
+        assert_eq!(
+            utils::serialise(&Data::from(pub_data.clone())),
+            utils::serialise(&(1u32, &value))
+        );
+        let parsed: Data = unwrap!(deserialise(&utils::serialise(&(1u32, &value))));
+        assert_eq!(parsed, Data::from(pub_data));
+
+        // encoded data serialised on its own falls back to the raw value
+        let compressed = UnpubData::new_with_encoding(value.clone(), owner, Encoding::Deflate);
+        let parsed: UnpubData = unwrap!(deserialise(&utils::serialise(&compressed)));
+        assert_eq!(parsed.encoding(), Encoding::Raw);
+        assert_eq!(parsed, unpub_data);
+    }
+}
@@ -34,13 +34,15 @@ mod identity;
 mod immutable_data;
 mod keys;
 mod mutable_data;
+mod owner_policy;
+mod permission_validity;
 mod request;
 mod response;
 mod sequence;
 mod utils;
 
 pub use coins::Coins;
-pub use errors::{EntryError, Error, Result};
+pub use errors::{EntryError, Error, HistoryIndex, Result};
 pub use identity::{
     app::{FullId as AppFullId, PublicId as AppPublicId},
     client::{FullId as ClientFullId, PublicId as ClientPublicId},
@@ -48,19 +50,32 @@ pub use identity::{
     PublicId,
 };
 pub use immutable_data::{
-    Address as IDataAddress, Data as IData, Kind as IDataKind, PubData as PubImmutableData,
-    UnpubData as UnpubImmutableData, MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
+    Address as IDataAddress, ChunkDetails as IDataChunkDetails, Data as IData, DataMap as IDataMap,
+    DedupStore as IDataDedupStore, Encoding as IDataEncoding, Kind as IDataKind,
+    PubData as PubImmutableData, Reference as IDataReference, UnpubData as UnpubImmutableData,
+    MAX_CHUNK_SIZE_IN_BYTES, MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
+    MAX_UNCOMPRESSED_IMMUTABLE_DATA_SIZE_IN_BYTES,
 };
 pub use keys::{BlsKeypair, BlsKeypairShare, Keypair, PublicKey, Signature};
 pub use mutable_data::{
-    Action as MDataAction, Address as MDataAddress, Data as MData, Entries as MDataEntries,
-    EntryActions as MDataEntryActions, Kind as MDataKind, PermissionSet as MDataPermissionSet,
+    Action as MDataAction, Address as MDataAddress, Change as MDataChange,
+    ChangeLog as MDataChangeLog, ConvergentData as ConvergentMutableData, Data as MData,
+    Entries as MDataEntries, EntryActions as MDataEntryActions, KeyRange as MDataKeyRange,
+    KeyScope as MDataKeyScope, Kind as MDataKind, MutationOperation as MDataMutationOperation,
+    Op as MDataOp, Operation as MDataOperation, Page as MDataPage,
+    PermissionSet as MDataPermissionSet, Precondition as MDataPrecondition,
     SeqData as SeqMutableData, SeqEntries as MDataSeqEntries,
     SeqEntryAction as MDataSeqEntryAction, SeqEntryActions as MDataSeqEntryActions,
-    SeqValue as MDataSeqValue, UnseqData as UnseqMutableData, UnseqEntries as MDataUnseqEntries,
-    UnseqEntryAction as MDataUnseqEntryAction, UnseqEntryActions as MDataUnseqEntryActions,
-    Value as MDataValue, Values as MDataValues,
+    SeqValue as MDataSeqValue, Stamp as MDataStamp, TypedSeqData as TypedSeqMutableData,
+    TypedUnseqData as TypedUnseqMutableData, UnseqData as UnseqMutableData,
+    UnseqEntries as MDataUnseqEntries, UnseqEntryAction as MDataUnseqEntryAction,
+    UnseqEntryActions as MDataUnseqEntryActions, User as MDataUser, Value as MDataValue,
+    ValueResults as MDataValueResults, Values as MDataValues, MAX_MUTABLE_DATA_ENTRIES,
+    MAX_MUTABLE_DATA_KEY_SIZE_IN_BYTES, MAX_MUTABLE_DATA_SIZE_IN_BYTES,
+    MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES,
 };
+pub use owner_policy::OwnerPolicy;
+pub use permission_validity::PermissionValidity;
 pub use request::{
     AuthorisationKind as RequestAuthKind, ClientRequest, CoinsRequest, IDataRequest, LoginPacket,
     LoginPacketRequest, MDataRequest, Request, SDataRequest, Type as RequestType,
@@ -68,13 +83,15 @@ pub use request::{
 };
 pub use response::{Response, TryFromError};
 pub use sequence::{
-    Action as SDataAction, Address as SDataAddress, Data as SData, Entries as SDataEntries,
-    Entry as SDataEntry, Index as SDataIndex, Indices as SDataIndices, Kind as SDataKind,
-    MutationOperation as SDataMutationOperation, Owner as SDataOwner,
+    Action as SDataAction, Address as SDataAddress, Checkpoint as SDataCheckpoint, Data as SData,
+    Delta as SDataDelta, Entries as SDataEntries, Entry as SDataEntry, EntryId as SDataEntryId,
+    Index as SDataIndex, Indices as SDataIndices, Kind as SDataKind,
+    MutationOperation as SDataMutationOperation, Owner as SDataOwner, Page as SDataPage,
     Permissions as SDataPermissions, PrivPermissions as SDataPrivPermissions, PrivSeqData,
     PrivUserPermissions as SDataPrivUserPermissions, PubPermissions as SDataPubPermissions,
-    PubSeqData, PubUserPermissions as SDataPubUserPermissions, User as SDataUser,
-    UserPermissions as SDataUserPermissions,
+    PubSeqData, PubUserPermissions as SDataPubUserPermissions,
+    SignedCheckpoint as SDataSignedCheckpoint, SignedOperation as SDataSignedOperation,
+    Summary as SDataSummary, User as SDataUser, UserPermissions as SDataUserPermissions,
 };
 pub use sha3::Sha3_512 as Ed25519Digest;
 pub use utils::verify_signature;
@@ -0,0 +1,474 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Convergent MutableData
+//!
+//! Convergent MutableData is an unsequenced key/value store backed by a last-writer-wins map CRDT.
+//! Every mutation is turned into a `MutationOperation` stamped with a Lamport clock and the key of
+//! its actor, and replicas which applied the same set of operations hold the same entries and
+//! permissions, whatever the order they applied them in.
+//!
+//! Deleted entries are kept as tombstones, so a deletion is not undone by a concurrent but older
+//! write received afterwards. As they are never dropped, tombstones count towards
+//! `MAX_MUTABLE_DATA_ENTRIES` and `MAX_MUTABLE_DATA_SIZE_IN_BYTES`.
+//!
+//! An operation is checked against the current permissions of the replica checking it, and their
+//! validity at its current clock, so a revoked or expired user can't get an operation accepted by
+//! backdating its stamp. A revocation thus takes effect on each replica as soon as it's applied,
+//! and replicas may disagree on an operation checked concurrently with it: the operations accepted
+//! by one replica are to be applied as they are by the others, with `apply_op`. The clock can only
+//! advance by one per operation checked. The limits and whether a write is an insertion or an
+//! update are also checked against the data held by the replica.
+
+use super::{check_size, Action, Address, Kind, PermissionSet, UnseqEntries, User};
+use crate::{Error, OwnerPolicy, PublicKey, Result, XorName};
+use bincode::serialized_size;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    fmt::{self, Debug, Formatter},
+};
+
+/// Timestamp of an operation on a convergent MutableData.
+///
+/// Stamps are ordered by their Lamport clock `counter` first, the `actor` breaking the ties
+/// between concurrent operations, so all replicas agree on which of two writes is the last one.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Stamp {
+    /// Lamport clock of the operation.
+    pub counter: u64,
+    /// Public key of the actor who issued the operation.
+    pub actor: PublicKey,
+}
+
+/// Mutation carried by an operation on a convergent MutableData.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Operation {
+    /// Sets the value of the entry at `key`, or deletes the entry if `value` is `None`.
+    Entry {
+        /// Key of the entry.
+        key: Vec<u8>,
+        /// New value of the entry.
+        value: Option<Vec<u8>>,
+    },
+    /// Sets the permissions of `user`, or deletes them if `permissions` is `None`.
+    Permissions {
+        /// User whose permissions are set.
+        user: User,
+        /// New permissions of the user.
+        permissions: Option<PermissionSet>,
+    },
+}
+
+/// CRDT operation on a convergent MutableData.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Op {
+    /// Stamp ordering the operation against the other writes to the same entry or user.
+    pub stamp: Stamp,
+    /// The mutation to apply.
+    pub operation: Operation,
+}
+
+/// Mutation operation to apply to a convergent MutableData.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MutationOperation {
+    /// Address of a convergent MutableData object on the network.
+    pub address: Address,
+    /// The operation to apply.
+    pub crdt_op: Op,
+}
+
+// Last-writer-wins register, holding a tombstone once its value is deleted.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+struct Register<T> {
+    stamp: Stamp,
+    value: Option<T>,
+}
+
+// Writes to the register at `key`, unless it holds a later write. Writes with the same stamp are
+// ordered by their value, so replicas converge even if an actor reused a stamp.
+fn write<K: Ord, T: Ord>(registers: &mut BTreeMap<K, Register<T>>, key: K, register: Register<T>) {
+    match registers.entry(key) {
+        Entry::Occupied(mut entry) => {
+            if *entry.get() < register {
+                let _ = entry.insert(register);
+            }
+        }
+        Entry::Vacant(entry) => {
+            let _ = entry.insert(register);
+        }
+    }
+}
+
+// Restores the register at `key` to its previous state.
+fn revert<K: Ord, T>(
+    registers: &mut BTreeMap<K, Register<T>>,
+    key: K,
+    register: Option<Register<T>>,
+) {
+    let _ = match register {
+        Some(register) => registers.insert(key, register),
+        None => registers.remove(&key),
+    };
+}
+
+// Register written by an operation, along with its state before the operation.
+enum Reverted {
+    Entry(Vec<u8>, Option<Register<Vec<u8>>>),
+    Permissions(User, Option<Register<PermissionSet>>),
+}
+
+/// Convergent MutableData, whose concurrent mutations are merged by every replica.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct ConvergentData {
+    /// Network address.
+    address: Address,
+    /// Entries, including the tombstones of the deleted ones.
+    data: BTreeMap<Vec<u8>, Register<Vec<u8>>>,
+    /// Permissions of the users, including the tombstones of the deleted ones.
+    permissions: BTreeMap<User, Register<PermissionSet>>,
+    /// Highest Lamport clock of the operations applied.
+    clock: u64,
+    /// Owner(s) of the data.
+    owner: OwnerPolicy,
+}
+
+impl Debug for ConvergentData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "ConvergentMutableData {:?}", self.name())
+    }
+}
+
+impl ConvergentData {
+    /// Creates a new convergent MutableData.
+    pub fn new(name: XorName, tag: u64, owner: impl Into<OwnerPolicy>) -> Self {
+        Self {
+            address: Address::Convergent { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            clock: 0,
+            owner: owner.into(),
+        }
+    }
+
+    /// Returns the address of the data.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name of the data.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+
+    /// Returns the tag type of the data.
+    pub fn tag(&self) -> u64 {
+        self.address.tag()
+    }
+
+    /// Returns the kind of the data.
+    pub fn kind(&self) -> Kind {
+        self.address.kind()
+    }
+
+    /// Returns the highest Lamport clock of the operations applied. The validity of permissions
+    /// is checked against it.
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Returns the owner(s) of the data.
+    pub fn owner(&self) -> &OwnerPolicy {
+        &self.owner
+    }
+
+    /// Returns the value of the entry at `key`, if it exists.
+    pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.data
+            .get(key)
+            .and_then(|register| register.value.as_ref())
+    }
+
+    /// Returns the keys of all the entries.
+    pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+        self.live_entries().map(|(key, _)| key.clone()).collect()
+    }
+
+    /// Returns the values of all the entries.
+    pub fn values(&self) -> Vec<Vec<u8>> {
+        self.live_entries()
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Returns all the entries.
+    pub fn entries(&self) -> UnseqEntries {
+        self.live_entries()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Returns the permissions of all the users.
+    pub fn permissions(&self) -> BTreeMap<User, PermissionSet> {
+        self.permissions
+            .iter()
+            .filter_map(|(user, register)| Some((*user, register.value.clone()?)))
+            .collect()
+    }
+
+    /// Returns the permissions of the provided user.
+    pub fn user_permissions(&self, user: impl Into<User>) -> Result<&PermissionSet> {
+        self.permissions
+            .get(&user.into())
+            .and_then(|register| register.value.as_ref())
+            .ok_or(Error::NoSuchKey)
+    }
+
+    /// Returns the size of this data after serialisation, including the tombstones.
+    pub fn serialised_size(&self) -> u64 {
+        serialized_size(self).unwrap_or(u64::MAX)
+    }
+
+    /// Returns `true` if the number of entries, including the tombstones, the size of each entry
+    /// and the size of this data are within the limits.
+    pub fn validate_size(&self) -> bool {
+        self.check_size().is_ok()
+    }
+
+    /// Checks if the provided user is an owner.
+    ///
+    /// Returns `Ok(())` on success and `Err(Error::AccessDenied)` if the user is not an owner.
+    pub fn check_is_owner(&self, requester: PublicKey) -> Result<()> {
+        if self.owner.is_satisfied_by(&requester) {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    /// Checks permissions for given `action` for the provided user. The action is allowed if
+    /// either the permissions for the user or the permissions for `Anyone` allow it, at the
+    /// current clock.
+    ///
+    /// Returns `Err(Error::AccessDenied)` if the permission check has failed, or
+    /// `Err(Error::PermissionExpired)` if the check has failed and some of the permissions are
+    /// not in effect at the current clock anymore.
+    pub fn check_permissions(&self, action: Action, requester: PublicKey) -> Result<()> {
+        if self.owner.is_satisfied_by(&requester) {
+            return Ok(());
+        }
+
+        let mut error = Error::AccessDenied;
+        for user in &[User::Key(requester), User::Anyone] {
+            if let Ok(permissions) = self.user_permissions(*user) {
+                match permissions.validity().check(self.clock) {
+                    Ok(()) if permissions.is_allowed(action) => return Ok(()),
+                    Err(Error::PermissionExpired) => error = Error::PermissionExpired,
+                    _ => (),
+                }
+            }
+        }
+        Err(error)
+    }
+
+    /// Sets the value of the entry at `key`, inserting it if it doesn't exist.
+    ///
+    /// Returns the operation to send to the other replicas.
+    pub fn set(
+        &mut self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+        requester: PublicKey,
+    ) -> Result<MutationOperation> {
+        self.apply_local(
+            Operation::Entry {
+                key,
+                value: Some(value),
+            },
+            requester,
+        )
+    }
+
+    /// Deletes the entry at `key`.
+    ///
+    /// Returns the operation to send to the other replicas, or `Err(Error::NoSuchEntry)` if the
+    /// entry doesn't exist.
+    pub fn del(&mut self, key: Vec<u8>, requester: PublicKey) -> Result<MutationOperation> {
+        if self.get(&key).is_none() {
+            return Err(Error::NoSuchEntry);
+        }
+        self.apply_local(Operation::Entry { key, value: None }, requester)
+    }
+
+    /// Inserts or updates the permissions of the provided user.
+    ///
+    /// Returns the operation to send to the other replicas.
+    pub fn set_user_permissions(
+        &mut self,
+        user: impl Into<User>,
+        permissions: PermissionSet,
+        requester: PublicKey,
+    ) -> Result<MutationOperation> {
+        self.apply_local(
+            Operation::Permissions {
+                user: user.into(),
+                permissions: Some(permissions),
+            },
+            requester,
+        )
+    }
+
+    /// Deletes the permissions of the provided user.
+    ///
+    /// Returns the operation to send to the other replicas, or `Err(Error::NoSuchKey)` if the
+    /// user has no permissions.
+    pub fn del_user_permissions(
+        &mut self,
+        user: impl Into<User>,
+        requester: PublicKey,
+    ) -> Result<MutationOperation> {
+        let user = user.into();
+        let _ = self.user_permissions(user)?;
+        self.apply_local(
+            Operation::Permissions {
+                user,
+                permissions: None,
+            },
+            requester,
+        )
+    }
+
+    /// Applies an operation without checking it, e.g. one received from another replica which
+    /// already checked it. Applying an operation more than once has no further effect.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the operation is not for this data.
+    pub fn apply_op(&mut self, op: MutationOperation) -> Result<()> {
+        if op.address != self.address {
+            return Err(Error::InvalidOperation);
+        }
+
+        let Op { stamp, operation } = op.crdt_op;
+        self.clock = self.clock.max(stamp.counter);
+        match operation {
+            Operation::Entry { key, value } => {
+                write(&mut self.data, key, Register { stamp, value })
+            }
+            Operation::Permissions { user, permissions } => write(
+                &mut self.permissions,
+                user,
+                Register {
+                    stamp,
+                    value: permissions,
+                },
+            ),
+        }
+        Ok(())
+    }
+
+    /// Applies an operation after checking it was issued by `requester`, who is allowed to
+    /// perform it by the current permissions.
+    ///
+    /// Returns:
+    /// `Ok(())` if the operation was applied,
+    /// `Err::InvalidOperation` if the operation is not for this data or not issued by `requester`,
+    /// `Err::InvalidSuccessor` with the current clock if the operation is stamped more than one
+    /// past it, i.e. some of the operations it follows haven't been applied yet,
+    /// `Err::AccessDenied` or `Err::PermissionExpired` if `requester` is not allowed to perform it,
+    /// `Err::TooManyEntries` or `Err::ExceededSize` if the data would then be over the limits.
+    pub fn apply_checked_op(&mut self, op: MutationOperation, requester: PublicKey) -> Result<()> {
+        let stamp = op.crdt_op.stamp;
+        if op.address != self.address || stamp.actor != requester {
+            return Err(Error::InvalidOperation);
+        }
+        if stamp.counter.saturating_sub(self.clock) > 1 {
+            return Err(Error::InvalidSuccessor(self.clock));
+        }
+        self.check_op(&stamp, &op.crdt_op.operation)?;
+        self.apply_within_limits(op)
+    }
+
+    // Checks the actor of `stamp` is allowed to perform `operation` by the current permissions.
+    fn check_op(&self, stamp: &Stamp, operation: &Operation) -> Result<()> {
+        let action = match operation {
+            Operation::Entry {
+                key,
+                value: Some(_),
+            } => {
+                if self.get(key).is_some() {
+                    Action::Update
+                } else {
+                    Action::Insert
+                }
+            }
+            Operation::Entry { value: None, .. } => Action::Delete,
+            Operation::Permissions { .. } => Action::ManagePermissions,
+        };
+        self.check_permissions(action, stamp.actor)
+    }
+
+    // Applies `op`, unless the data would then be over the limits.
+    fn apply_within_limits(&mut self, op: MutationOperation) -> Result<()> {
+        let clock = self.clock;
+        let reverted = match &op.crdt_op.operation {
+            Operation::Entry { key, .. } => {
+                Reverted::Entry(key.clone(), self.data.get(key).cloned())
+            }
+            Operation::Permissions { user, .. } => {
+                Reverted::Permissions(*user, self.permissions.get(user).cloned())
+            }
+        };
+        self.apply_op(op)?;
+        self.check_size().inspect_err(|_| {
+            self.clock = clock;
+            match reverted {
+                Reverted::Entry(key, register) => revert(&mut self.data, key, register),
+                Reverted::Permissions(user, register) => {
+                    revert(&mut self.permissions, user, register)
+                }
+            }
+        })
+    }
+
+    // Checks the number of entries, including the tombstones, the size of each entry and the
+    // size of this data against the limits.
+    fn check_size(&self) -> Result<()> {
+        check_size(
+            &self.data,
+            |register| register.value.as_ref().map_or(0, Vec::len),
+            self.serialised_size(),
+        )
+    }
+
+    // Checks and applies an operation issued by `requester` on this replica.
+    //
+    // Returns `Err(Error::InvalidOperation)` if the clock can't be advanced anymore.
+    fn apply_local(
+        &mut self,
+        operation: Operation,
+        requester: PublicKey,
+    ) -> Result<MutationOperation> {
+        let stamp = Stamp {
+            counter: self.clock.checked_add(1).ok_or(Error::InvalidOperation)?,
+            actor: requester,
+        };
+        self.check_op(&stamp, &operation)?;
+        let op = MutationOperation {
+            address: self.address,
+            crdt_op: Op { stamp, operation },
+        };
+        self.apply_within_limits(op.clone())?;
+        Ok(op)
+    }
+
+    fn live_entries(&self) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+        self.data
+            .iter()
+            .filter_map(|(key, register)| Some((key, register.value.as_ref()?)))
+    }
+}
@@ -0,0 +1,2907 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! MutableData
+//!
+//! MutableData can be either published or unpublished, and either sequenced or unsequenced.
+//!
+//! ## Published and unpublished data
+//!
+//! Unpublished MutableData can only be read by its owners and by users granted
+//! `Action::Read`. Published MutableData (`PubSeq` and `PubUnseq`) can be read by anyone, while
+//! mutating it is still gated by its owners and permissions, including those granted to
+//! `User::Anyone`.
+//!
+//! Please see `append_only_data.rs` for more about unpublished versus published data.
+//!
+//! ## Sequenced and unsequenced data.
+//!
+//! Explicitly sequencing all mutations is an option provided for clients to allow them to avoid
+//! dealing with conflicting mutations. However, we don't need the version for preventing replay
+//! attacks.
+//!
+//! For sequenced MutableData the client must specify the next version number of a value while
+//! modifying/deleting keys. Similarly, while modifying the MutableData shell (permissions,
+//! ownership, etc.), the next version number must be passed. For unsequenced MutableData the client
+//! does not have to pass version numbers for keys, but it still must pass the next version number
+//! while modifying the MutableData shell.
+//!
+//! ## Convergent data
+//!
+//! Convergent MutableData is unsequenced data whose concurrent mutations are merged by every
+//! replica instead of being applied in the order they are received. Please see `convergent.rs`.
+//!
+//! ## Typed entries
+//!
+//! Entries can be read and mutated as serde keys and values through the typed views in `typed.rs`.
+
+mod convergent;
+mod typed;
+
+pub use convergent::{ConvergentData, MutationOperation, Op, Operation, Stamp};
+pub use typed::{TypedSeqData, TypedUnseqData};
+
+use crate::{
+    utils, EntryError, Error, OwnerPolicy, PermissionValidity, PublicKey, Result, Signature,
+    XorName,
+};
+use bincode::serialized_size;
+use hex_fmt::HexFmt;
+use multibase::Decodable;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque},
+    fmt::{self, Debug, Formatter},
+    mem,
+    ops::Bound,
+    result,
+};
+
+/// Maximum allowed number of entries in a MutableData.
+pub const MAX_MUTABLE_DATA_ENTRIES: u64 = 1000;
+/// Maximum allowed size of the key of a MutableData entry.
+pub const MAX_MUTABLE_DATA_KEY_SIZE_IN_BYTES: u64 = 1024;
+/// Maximum allowed size of the value of a MutableData entry.
+pub const MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES: u64 = 100 * 1024;
+/// Maximum allowed size for a serialised MutableData to grow to.
+pub const MAX_MUTABLE_DATA_SIZE_IN_BYTES: u64 = 1024 * 1024;
+
+/// MutableData that is unpublished on the network. This data can only be fetched by the owner or
+/// those in the permissions fields with `Permission::Read` access.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct SeqData {
+    /// Network address.
+    address: Address,
+    /// Key-Value semantics.
+    data: SeqEntries,
+    /// Maps an application key to a list of allowed or forbidden actions.
+    permissions: BTreeMap<User, PermissionSet>,
+    /// Maps an application key to the actions allowed on the entries within given scopes only.
+    scoped_permissions: BTreeMap<PublicKey, BTreeMap<KeyScope, PermissionSet>>,
+    /// Version should be increased for any changes to MutableData fields except for data.
+    version: u64,
+    /// Number of mutations of the entries. Along with the version, it counts the mutations
+    /// permission validity bounds are checked against.
+    entries_mutations: u64,
+    /// Contains the policy defining the owner or owners of this data.
+    ///
+    /// Data Handlers in vaults enforce that a mutation request has a valid signature of the owner.
+    owner: OwnerPolicy,
+    /// Owner(s) the data is being transferred to, who have yet to accept it.
+    pending_owner: Option<OwnerPolicy>,
+    /// Log of the latest mutations of the entries, if enabled.
+    change_log: Option<ChangeLog>,
+}
+
+impl Debug for SeqData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SeqMutableData {:?}", self.name())
+    }
+}
+
+/// MutableData that is unpublished on the network. This data can only be fetched by the owner or
+/// those in the permissions fields with `Permission::Read` access.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct UnseqData {
+    /// Network address.
+    address: Address,
+    /// Key-Value semantics.
+    data: UnseqEntries,
+    /// Maps an application key to a list of allowed or forbidden actions.
+    permissions: BTreeMap<User, PermissionSet>,
+    /// Maps an application key to the actions allowed on the entries within given scopes only.
+    scoped_permissions: BTreeMap<PublicKey, BTreeMap<KeyScope, PermissionSet>>,
+    /// Version should be increased for any changes to MutableData fields except for data.
+    version: u64,
+    /// Number of mutations of the entries. Along with the version, it counts the mutations
+    /// permission validity bounds are checked against.
+    entries_mutations: u64,
+    /// Contains the policy defining the owner or owners of this data.
+    ///
+    /// Data Handlers in vaults enforce that a mutation request has a valid signature of the owner.
+    owner: OwnerPolicy,
+    /// Owner(s) the data is being transferred to, who have yet to accept it.
+    pending_owner: Option<OwnerPolicy>,
+    /// Log of the latest mutations of the entries, if enabled.
+    change_log: Option<ChangeLog>,
+}
+
+impl Debug for UnseqData {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "UnseqMutableData {:?}", self.name())
+    }
+}
+
+/// A value in sequenced MutableData.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct SeqValue {
+    /// Actual data.
+    pub data: Vec<u8>,
+    /// Version, incremented sequentially for any change to `data`.
+    pub version: u64,
+}
+
+impl Debug for SeqValue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:<8} :: {}", HexFmt(&self.data), self.version)
+    }
+}
+
+/// Wrapper type for values, which can be sequenced or unsequenced.
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub enum Value {
+    /// Sequenced value.
+    Seq(SeqValue),
+    /// Unsequenced value.
+    Unseq(Vec<u8>),
+}
+
+impl From<SeqValue> for Value {
+    fn from(value: SeqValue) -> Self {
+        Value::Seq(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Unseq(value)
+    }
+}
+
+/// Wrapper type for lists of sequenced or unsequenced values.
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub enum Values {
+    /// List of sequenced values.
+    Seq(Vec<SeqValue>),
+    /// List of unsequenced values.
+    Unseq(Vec<Vec<u8>>),
+}
+
+impl From<Vec<SeqValue>> for Values {
+    fn from(values: Vec<SeqValue>) -> Self {
+        Values::Seq(values)
+    }
+}
+
+impl From<Vec<Vec<u8>>> for Values {
+    fn from(values: Vec<Vec<u8>>) -> Self {
+        Values::Unseq(values)
+    }
+}
+
+/// Results of getting values by key, holding `EntryError::NoSuchEntry` for the missing keys.
+pub type ValueResults = BTreeMap<Vec<u8>, result::Result<Value, EntryError>>;
+
+/// Set of user permissions.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct PermissionSet {
+    permissions: BTreeSet<Action>,
+    validity: PermissionValidity,
+}
+
+impl PermissionSet {
+    /// Constructs new permission set.
+    pub fn new() -> PermissionSet {
+        PermissionSet {
+            permissions: Default::default(),
+            validity: Default::default(),
+        }
+    }
+
+    /// Allows the given action.
+    pub fn allow(mut self, action: Action) -> Self {
+        let _ = self.permissions.insert(action);
+        self
+    }
+
+    /// Denies the given action.
+    pub fn deny(mut self, action: Action) -> Self {
+        let _ = self.permissions.remove(&action);
+        self
+    }
+
+    /// Grants the permissions from the given number of mutations of the MutableData on, as
+    /// returned by its `mutations()`.
+    pub fn valid_from(mut self, mutations: u64) -> Self {
+        self.validity.not_before = Some(mutations);
+        self
+    }
+
+    /// Grants the permissions up to and including the given number of mutations of the
+    /// MutableData, as returned by its `mutations()`. Mutations of the entries count as well, so
+    /// the grant can't be used indefinitely without changing the fields of the data.
+    pub fn valid_until(mut self, mutations: u64) -> Self {
+        self.validity.not_after = Some(mutations);
+        self
+    }
+
+    /// Returns the numbers of mutations of the MutableData within which the permissions are in
+    /// effect.
+    pub fn validity(&self) -> PermissionValidity {
+        self.validity
+    }
+
+    /// Is the given action allowed according to this permission set?
+    pub fn is_allowed(&self, action: Action) -> bool {
+        self.permissions.contains(&action)
+    }
+}
+
+/// Set of Actions that can be performed on the MutableData.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// Permission to read entries.
+    Read,
+    /// Permission to insert new entries.
+    Insert,
+    /// Permission to update existing entries.
+    Update,
+    /// Permission to delete existing entries.
+    Delete,
+    /// Permission to modify permissions for other users.
+    ManagePermissions,
+}
+
+/// User that can access MutableData.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum User {
+    /// Any user.
+    Anyone,
+    /// User identified by its public key.
+    Key(PublicKey),
+}
+
+impl From<PublicKey> for User {
+    fn from(key: PublicKey) -> Self {
+        User::Key(key)
+    }
+}
+
+/// Scope of the entries of MutableData which permissions apply to.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum KeyScope {
+    /// The entry with the given key.
+    Key(Vec<u8>),
+    /// The entries with keys starting with the given prefix.
+    Prefix(Vec<u8>),
+}
+
+impl KeyScope {
+    /// Returns `true` if the entry with the given key is within this scope.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        match self {
+            KeyScope::Key(scope) => scope[..] == *key,
+            KeyScope::Prefix(prefix) => key.starts_with(prefix),
+        }
+    }
+}
+
+/// Mutation of the entries of MutableData, as recorded in its change log.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Change {
+    /// Version of the change log after this change.
+    pub version: u64,
+    /// Key of the requester who made the change.
+    pub requester: PublicKey,
+    /// Entry actions of the change.
+    pub actions: EntryActions,
+    /// Versions of the mutated entries after the change, for sequenced data.
+    pub entry_versions: BTreeMap<Vec<u8>, u64>,
+}
+
+/// Log of the latest mutations of the entries of MutableData, bounded by its capacity.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChangeLog {
+    capacity: u64,
+    version: u64,
+    changes: VecDeque<Change>,
+}
+
+impl ChangeLog {
+    /// Constructs an empty change log which keeps up to `capacity` changes.
+    ///
+    /// Returns `Err::InvalidOperation` if `capacity` is zero or more than
+    /// `MAX_MUTABLE_DATA_ENTRIES`.
+    pub fn new(capacity: u64) -> Result<Self> {
+        if capacity == 0 || capacity > MAX_MUTABLE_DATA_ENTRIES {
+            return Err(Error::InvalidOperation);
+        }
+        Ok(Self {
+            capacity,
+            version: 0,
+            changes: VecDeque::new(),
+        })
+    }
+
+    /// Returns the maximum number of changes kept.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the number of changes ever recorded.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the changes kept, from the oldest.
+    pub fn changes(&self) -> &VecDeque<Change> {
+        &self.changes
+    }
+
+    /// Returns the changes kept which were made after the given version of the change log.
+    pub fn since(&self, since_version: u64) -> Vec<Change> {
+        self.changes
+            .iter()
+            .filter(|change| change.version > since_version)
+            .cloned()
+            .collect()
+    }
+
+    // Records a change, dropping and returning the oldest one if the log is full.
+    fn push(&mut self, requester: PublicKey, actions: EntryActions) -> Option<Change> {
+        let entry_versions = match &actions {
+            EntryActions::Seq(actions) => actions
+                .actions()
+                .iter()
+                .map(|(key, action)| (key.clone(), action.version()))
+                .collect(),
+            EntryActions::Unseq(_) => BTreeMap::new(),
+        };
+        self.version += 1;
+        self.changes.push_back(Change {
+            version: self.version,
+            requester,
+            actions,
+            entry_versions,
+        });
+        if self.changes.len() as u64 > self.capacity {
+            self.changes.pop_front()
+        } else {
+            None
+        }
+    }
+
+    // Reverts the latest change recorded, restoring the `dropped` one.
+    fn revert(&mut self, dropped: Option<Change>) {
+        let _ = self.changes.pop_back();
+        self.version -= 1;
+        if let Some(dropped) = dropped {
+            self.changes.push_front(dropped);
+        }
+    }
+}
+
+/// Range of keys to read from MutableData.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct KeyRange {
+    /// First key of the range, or the first key of the data if `None`.
+    pub start: Option<Vec<u8>>,
+    /// Key right after the end of the range, or unbounded if `None`.
+    pub end: Option<Vec<u8>>,
+    /// Maximum number of entries to read, which must not be zero, or unlimited if `None`.
+    pub limit: Option<u64>,
+    /// Whether to read the range from its end.
+    pub reverse: bool,
+    /// Continuation token of a previous page of this range.
+    pub continuation: Option<Vec<u8>>,
+}
+
+impl KeyRange {
+    /// Constructs the range of keys from `start` (inclusive) to `end` (exclusive).
+    pub fn new(start: impl Into<Option<Vec<u8>>>, end: impl Into<Option<Vec<u8>>>) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Constructs the range of all the keys starting with `prefix`.
+    pub fn prefix(prefix: Vec<u8>) -> Self {
+        // The end of the range is the first key greater than all the keys with the prefix,
+        // which does not exist if the prefix only holds `0xff` bytes.
+        let mut end = prefix.clone();
+        while let Some(last) = end.pop() {
+            if last < u8::MAX {
+                end.push(last + 1);
+                break;
+            }
+        }
+        let end = if end.is_empty() { None } else { Some(end) };
+        Self::new(prefix, end)
+    }
+
+    /// Limits the number of entries to read.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Reads the range from its end.
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Continues reading the range after a previous page.
+    pub fn continue_from(mut self, continuation: Vec<u8>) -> Self {
+        self.continuation = Some(continuation);
+        self
+    }
+}
+
+/// A page of entries from a range of keys of MutableData.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Page {
+    /// Entries of the page, held in key order whichever way the range is read.
+    pub entries: Entries,
+    /// Token to continue reading the range from, or `None` if the range has been exhausted.
+    pub continuation: Option<Vec<u8>>,
+}
+
+// Reads the page of entries of `data` within `range`, with the continuation token if the limit
+// was reached before exhausting the range. A zero limit is rejected, as no continuation token
+// could be returned for a page that reads no entry.
+fn range_entries<V: Clone>(data: &BTreeMap<Vec<u8>, V>, range: &KeyRange) -> Result<Page>
+where
+    BTreeMap<Vec<u8>, V>: Into<Entries>,
+{
+    if range.limit == Some(0) {
+        return Err(Error::InvalidOperation);
+    }
+
+    // The continuation token is the last key read, so reading continues past it, unless it is
+    // outside of the range.
+    let continuation = range.continuation.as_ref();
+    let lower = match (range.start.as_ref(), continuation) {
+        (start, Some(continuation))
+            if !range.reverse && !matches!(start, Some(start) if continuation < start) =>
+        {
+            Bound::Excluded(continuation)
+        }
+        (Some(start), _) => Bound::Included(start),
+        (None, _) => Bound::Unbounded,
+    };
+    let upper = match (range.end.as_ref(), continuation) {
+        (end, Some(continuation))
+            if range.reverse && !matches!(end, Some(end) if continuation >= end) =>
+        {
+            Bound::Excluded(continuation)
+        }
+        (Some(end), _) => Bound::Excluded(end),
+        (None, _) => Bound::Unbounded,
+    };
+
+    // An upper bound is always excluded.
+    if let (Bound::Included(lower) | Bound::Excluded(lower), Bound::Excluded(upper)) =
+        (lower, upper)
+    {
+        if lower >= upper {
+            return Ok(Page {
+                entries: BTreeMap::new().into(),
+                continuation: None,
+            });
+        }
+    }
+
+    let limit = range.limit.unwrap_or(u64::MAX) as usize;
+    let entries: Box<dyn Iterator<Item = (&Vec<u8>, &V)>> = if range.reverse {
+        Box::new(data.range::<Vec<u8>, _>((lower, upper)).rev())
+    } else {
+        Box::new(data.range::<Vec<u8>, _>((lower, upper)))
+    };
+    let mut entries = entries.peekable();
+    let page: BTreeMap<_, _> = entries
+        .by_ref()
+        .take(limit)
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    let continuation = if page.is_empty() || entries.peek().is_none() {
+        None
+    } else if range.reverse {
+        page.keys().next().cloned()
+    } else {
+        page.keys().next_back().cloned()
+    };
+    Ok(Page {
+        entries: page.into(),
+        continuation,
+    })
+}
+
+// Checks the number of `entries`, the size of their keys and values, and the `serialised_size`
+// of the data holding them against the limits.
+fn check_size<V>(
+    entries: &BTreeMap<Vec<u8>, V>,
+    value_size: impl Fn(&V) -> usize,
+    serialised_size: u64,
+) -> Result<()> {
+    if entries.len() as u64 > MAX_MUTABLE_DATA_ENTRIES {
+        return Err(Error::TooManyEntries);
+    }
+    if entries.iter().any(|(key, value)| {
+        key.len() as u64 > MAX_MUTABLE_DATA_KEY_SIZE_IN_BYTES
+            || value_size(value) as u64 > MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES
+    }) || serialised_size > MAX_MUTABLE_DATA_SIZE_IN_BYTES
+    {
+        return Err(Error::ExceededSize);
+    }
+    Ok(())
+}
+
+macro_rules! impl_mutable_data {
+    ($flavour:ident, $value:ty) => {
+        impl $flavour {
+            /// Returns the address.
+            pub fn address(&self) -> &Address {
+                &self.address
+            }
+
+            /// Returns the name.
+            pub fn name(&self) -> &XorName {
+                self.address.name()
+            }
+
+            /// Returns the tag type.
+            pub fn tag(&self) -> u64 {
+                self.address.tag()
+            }
+
+            /// Returns the kind.
+            pub fn kind(&self) -> Kind {
+                self.address.kind()
+            }
+
+            /// Returns the version of the MutableData fields (not the data version).
+            pub fn version(&self) -> u64 {
+                self.version
+            }
+
+            /// Returns the number of mutations of the data, of its fields and its entries alike.
+            /// Permission validity bounds are checked against it.
+            pub fn mutations(&self) -> u64 {
+                self.version + self.entries_mutations
+            }
+
+            /// Returns the owner policy.
+            pub fn owner(&self) -> &OwnerPolicy {
+                &self.owner
+            }
+
+            /// Returns all the keys in the data.
+            pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+                self.data.keys().cloned().collect()
+            }
+
+            /// Returns size of this data after serialisation.
+            pub fn serialised_size(&self) -> u64 {
+                serialized_size(self).unwrap_or(u64::MAX)
+            }
+
+            /// Returns `true` if the number of entries, the size of each entry and the size of
+            /// this data, including its change log, are within the limits.
+            pub fn validate_size(&self) -> bool {
+                self.check_size().is_ok()
+            }
+
+            /// Returns the change log, if enabled.
+            pub fn change_log(&self) -> Option<&ChangeLog> {
+                self.change_log.as_ref()
+            }
+
+            /// Enables the change log, keeping up to `capacity` changes, or disables it if
+            /// `capacity` is `None`. Changing the capacity of an enabled change log keeps its
+            /// latest changes.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned. Returns `Err::InvalidOperation` if
+            /// `capacity` is zero or more than `MAX_MUTABLE_DATA_ENTRIES`.
+            pub fn set_change_log_capacity(
+                &mut self,
+                capacity: Option<u64>,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                let change_log = match capacity {
+                    Some(capacity) => {
+                        let mut change_log = ChangeLog::new(capacity)?;
+                        if let Some(old) = self.change_log.take() {
+                            change_log.version = old.version;
+                            change_log.changes = old.changes;
+                            while change_log.changes.len() as u64 > capacity {
+                                let _ = change_log.changes.pop_front();
+                            }
+                        }
+                        Some(change_log)
+                    }
+                    None => None,
+                };
+                self.change_log = change_log;
+                self.version = version;
+
+                Ok(())
+            }
+
+            // Replaces the entries with `data` and records the change made by `requester` in the
+            // change log, if enabled, unless that makes this data exceed the limits.
+            fn replace_entries(
+                &mut self,
+                data: BTreeMap<Vec<u8>, $value>,
+                requester: PublicKey,
+                logged_actions: Option<EntryActions>,
+            ) -> Result<()> {
+                let old_data = mem::replace(&mut self.data, data);
+                let dropped = match (self.change_log.as_mut(), logged_actions) {
+                    (Some(change_log), Some(actions)) => Some(change_log.push(requester, actions)),
+                    _ => None,
+                };
+                self.check_size().map_err(|error| {
+                    self.data = old_data;
+                    if let (Some(change_log), Some(dropped)) = (self.change_log.as_mut(), dropped) {
+                        change_log.revert(dropped);
+                    }
+                    error
+                })?;
+                self.entries_mutations += 1;
+                Ok(())
+            }
+
+            /// Returns a page of the entries within `range`.
+            ///
+            /// Returns `Err::InvalidOperation` if the limit of `range` is zero.
+            pub fn range(&self, range: &KeyRange) -> Result<Page> {
+                range_entries(&self.data, range)
+            }
+
+            /// Returns the shell of this MutableData (the fields without the data and its change
+            /// log).
+            pub fn shell(&self) -> Self {
+                Self {
+                    address: self.address.clone(),
+                    data: BTreeMap::new(),
+                    permissions: self.permissions.clone(),
+                    scoped_permissions: self.scoped_permissions.clone(),
+                    version: self.version,
+                    entries_mutations: self.entries_mutations,
+                    owner: self.owner.clone(),
+                    pending_owner: self.pending_owner.clone(),
+                    change_log: None,
+                }
+            }
+
+            /// Gets a complete list of permissions.
+            pub fn permissions(&self) -> BTreeMap<User, PermissionSet> {
+                self.permissions.clone()
+            }
+
+            /// Gets the permissions for the provided user.
+            pub fn user_permissions(&self, user: impl Into<User>) -> Result<&PermissionSet> {
+                self.permissions.get(&user.into()).ok_or(Error::NoSuchKey)
+            }
+
+            /// Gets the permissions scoped to some of the entries for the provided user.
+            pub fn user_scoped_permissions(
+                &self,
+                user: PublicKey,
+            ) -> Result<&BTreeMap<KeyScope, PermissionSet>> {
+                self.scoped_permissions.get(&user).ok_or(Error::NoSuchKey)
+            }
+
+            /// Checks if the provided user can act as the owner(s) on its own.
+            ///
+            /// Returns `Ok(())` on success and `Err(Error::AccessDenied)` if the user is not an
+            /// owner.
+            pub fn check_is_owner(&self, requester: PublicKey) -> Result<()> {
+                if self.owner.is_satisfied_by(&requester) {
+                    Ok(())
+                } else {
+                    Err(Error::AccessDenied)
+                }
+            }
+
+            /// Checks if `signatures` of `message` are enough to act as the owner(s), either as a
+            /// set of signatures of the owner keys or as a combined threshold signature.
+            ///
+            /// Returns `Ok(())` on success, `Err(Error::InvalidSignature)` if a signature of an
+            /// owner key is not valid and `Err(Error::AccessDenied)` if not enough owner keys
+            /// signed.
+            pub fn check_is_owner_with_signatures(
+                &self,
+                signatures: &BTreeMap<PublicKey, Signature>,
+                message: &[u8],
+            ) -> Result<()> {
+                self.owner.verify(signatures, message)
+            }
+
+            /// Checks permissions for given `action` for the provided user. The action is
+            /// allowed if either the permissions for the user or the permissions for `Anyone`
+            /// allow it, and reading public data is always allowed.
+            ///
+            /// Returns `Err(Error::AccessDenied)` if the permission check has failed, or
+            /// `Err(Error::PermissionExpired)` if the check has failed and some of the
+            /// permissions are not in effect at the current number of mutations anymore.
+            pub fn check_permissions(&self, action: Action, requester: PublicKey) -> Result<()> {
+                if self.owner.is_satisfied_by(&requester) {
+                    Ok(())
+                } else {
+                    self.check_user_permissions(action, requester)
+                }
+            }
+
+            // Checks permissions for given `action` for the provided user, regardless of the
+            // owner.
+            fn check_user_permissions(&self, action: Action, requester: PublicKey) -> Result<()> {
+                if action == Action::Read && self.address.is_pub() {
+                    return Ok(());
+                }
+
+                let mut error = Error::AccessDenied;
+                for user in &[User::Key(requester), User::Anyone] {
+                    if let Some(permissions) = self.permissions.get(user) {
+                        match permissions.validity().check(self.mutations()) {
+                            Ok(()) if permissions.is_allowed(action) => return Ok(()),
+                            Err(Error::PermissionExpired) => error = Error::PermissionExpired,
+                            _ => (),
+                        }
+                    }
+                }
+                Err(error)
+            }
+
+            /// Checks permissions for given `action` on the entry with the given `key` for the
+            /// provided user, allowing it if either the permissions for the whole data or the
+            /// permissions for any scope containing the entry allow it.
+            ///
+            /// Returns `Err(Error::AccessDenied)` if the permission check has failed, or
+            /// `Err(Error::PermissionExpired)` if the check has failed and some of the user's
+            /// permissions are not in effect at the current number of mutations anymore.
+            pub fn check_key_permissions(
+                &self,
+                action: Action,
+                requester: PublicKey,
+                key: &[u8],
+            ) -> Result<()> {
+                let mut error = match self.check_permissions(action, requester) {
+                    Ok(()) => return Ok(()),
+                    Err(error) => error,
+                };
+                let scoped = self
+                    .scoped_permissions
+                    .get(&requester)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(scope, _)| scope.contains(key));
+                for (_, permissions) in scoped {
+                    match permissions.validity().check(self.mutations()) {
+                        Ok(()) if permissions.is_allowed(action) => return Ok(()),
+                        Err(Error::PermissionExpired) => error = Error::PermissionExpired,
+                        _ => (),
+                    }
+                }
+                Err(error)
+            }
+
+            /// Inserts or updates permissions for the provided user.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn set_user_permissions(
+                &mut self,
+                user: impl Into<User>,
+                permissions: PermissionSet,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                let _prev = self.permissions.insert(user.into(), permissions);
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Deletes permissions for the provided user.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn del_user_permissions(
+                &mut self,
+                user: impl Into<User>,
+                version: u64,
+            ) -> Result<()> {
+                let user = user.into();
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+                if !self.permissions.contains_key(&user) {
+                    return Err(Error::NoSuchKey);
+                }
+
+                let _ = self.permissions.remove(&user);
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Inserts or updates permissions scoped to some of the entries for the provided
+            /// user.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn set_scoped_permissions(
+                &mut self,
+                user: PublicKey,
+                scope: KeyScope,
+                permissions: PermissionSet,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                let _prev = self
+                    .scoped_permissions
+                    .entry(user)
+                    .or_default()
+                    .insert(scope, permissions);
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Deletes permissions scoped to some of the entries for the provided user.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn del_scoped_permissions(
+                &mut self,
+                user: PublicKey,
+                scope: &KeyScope,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+                let scoped = self
+                    .scoped_permissions
+                    .get_mut(&user)
+                    .ok_or(Error::NoSuchKey)?;
+                if scoped.remove(scope).is_none() {
+                    return Err(Error::NoSuchKey);
+                }
+                if scoped.is_empty() {
+                    let _ = self.scoped_permissions.remove(&user);
+                }
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Deletes user permissions without performing any validation.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn del_user_permissions_without_validation(
+                &mut self,
+                user: impl Into<User>,
+                version: u64,
+            ) -> bool {
+                if version <= self.version {
+                    return false;
+                }
+
+                let _ = self.permissions.remove(&user.into());
+                self.version = version;
+
+                true
+            }
+
+            /// Changes the owner.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn change_owner(
+                &mut self,
+                new_owner: impl Into<OwnerPolicy>,
+                version: u64,
+            ) -> Result<()> {
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                self.owner = new_owner.into();
+                self.pending_owner = None;
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Returns the owner(s) the data is being transferred to, if any.
+            pub fn pending_owner(&self) -> Option<&OwnerPolicy> {
+                self.pending_owner.as_ref()
+            }
+
+            /// Starts transferring the data to `new_owner`, who then has to accept it. A pending
+            /// transfer is replaced.
+            ///
+            /// Returns:
+            /// `Err::AccessDenied` if `requester` cannot act as the owner(s),
+            /// `Err::InvalidSuccessor` if `version` is not the current version + 1.
+            pub fn propose_owner(
+                &mut self,
+                new_owner: impl Into<OwnerPolicy>,
+                requester: PublicKey,
+                version: u64,
+            ) -> Result<()> {
+                self.check_is_owner(requester)?;
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                self.pending_owner = Some(new_owner.into());
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Completes the transfer of the data to the pending owner(s), on behalf of whom
+            /// `requester` accepts it.
+            ///
+            /// Returns:
+            /// `Err::InvalidOperation` if no transfer is pending,
+            /// `Err::AccessDenied` if `requester` cannot act as the pending owner(s),
+            /// `Err::InvalidSuccessor` if `version` is not the current version + 1.
+            pub fn accept_owner(&mut self, requester: PublicKey, version: u64) -> Result<()> {
+                let new_owner = match self.pending_owner {
+                    None => return Err(Error::InvalidOperation),
+                    Some(ref owner) if !owner.is_satisfied_by(&requester) => {
+                        return Err(Error::AccessDenied)
+                    }
+                    Some(ref owner) => owner.clone(),
+                };
+                if version != self.version + 1 {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+
+                self.owner = new_owner;
+                self.pending_owner = None;
+                self.version = version;
+
+                Ok(())
+            }
+
+            /// Changes the owner without performing any validation.
+            ///
+            /// Requires the new `version` of the MutableData fields. If it does not match the
+            /// current version + 1, an error will be returned.
+            pub fn change_owner_without_validation(
+                &mut self,
+                new_owner: impl Into<OwnerPolicy>,
+                version: u64,
+            ) -> bool {
+                if version <= self.version {
+                    return false;
+                }
+
+                self.owner = new_owner.into();
+                self.pending_owner = None;
+                self.version = version;
+
+                true
+            }
+
+            /// Returns true if `action` is allowed for the provided user at the current number of
+            /// mutations.
+            pub fn is_action_allowed(&self, requester: &PublicKey, action: Action) -> bool {
+                self.check_user_permissions(action, *requester).is_ok()
+            }
+        }
+    };
+}
+
+impl_mutable_data!(SeqData, SeqValue);
+impl_mutable_data!(UnseqData, Vec<u8>);
+
+impl UnseqData {
+    /// Creates a new unsequenced MutableData.
+    pub fn new(name: XorName, tag: u64, owner: impl Into<OwnerPolicy>) -> Self {
+        Self {
+            address: Address::Unseq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        }
+    }
+
+    /// Creates a new unsequenced MutableData, readable by anyone.
+    pub fn new_pub(name: XorName, tag: u64, owner: impl Into<OwnerPolicy>) -> Self {
+        Self {
+            address: Address::PubUnseq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        }
+    }
+
+    /// Creates a new unsequenced MutableData with entries and permissions.
+    ///
+    /// Returns `Err::TooManyEntries` or `Err::ExceededSize` if the data exceeds the limits.
+    pub fn new_with_data(
+        name: XorName,
+        tag: u64,
+        data: UnseqEntries,
+        permissions: BTreeMap<User, PermissionSet>,
+        owner: impl Into<OwnerPolicy>,
+    ) -> Result<Self> {
+        let data = Self {
+            address: Address::Unseq { name, tag },
+            data,
+            permissions,
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        };
+        data.check_size()?;
+        Ok(data)
+    }
+
+    // Checks the data against the limits.
+    fn check_size(&self) -> Result<()> {
+        check_size(&self.data, |value| value.len(), self.serialised_size())
+    }
+
+    /// Returns a value for the given key.
+    pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.data.get(key)
+    }
+
+    /// Returns values of all entries.
+    pub fn values(&self) -> Vec<Vec<u8>> {
+        self.data.values().cloned().collect()
+    }
+
+    /// Returns all entries.
+    pub fn entries(&self) -> &UnseqEntries {
+        &self.data
+    }
+
+    /// Removes and returns all entries.
+    pub fn take_entries(&mut self) -> UnseqEntries {
+        mem::replace(&mut self.data, BTreeMap::new())
+    }
+
+    /// Mutates entries based on `actions` for the provided user. None of the actions are
+    /// performed unless all the preconditions hold, and checking preconditions requires the
+    /// permission to read. Each action is checked against the permissions for its key.
+    ///
+    /// Returns `Err(InvalidEntryActions)` if a precondition does not hold or if the mutation
+    /// parameters are invalid, and `Err(TooManyEntries)` or `Err(ExceededSize)` if the mutated
+    /// data would exceed the limits.
+    pub fn mutate_entries(
+        &mut self,
+        actions: UnseqEntryActions,
+        requester: PublicKey,
+    ) -> Result<()> {
+        let logged_actions = self.change_log.as_ref().map(|_| actions.clone());
+        let UnseqEntryActions {
+            actions,
+            preconditions,
+        } = actions;
+        let (insert, update, delete) = actions.into_iter().fold(
+            (
+                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
+                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
+                BTreeSet::<Vec<u8>>::new(),
+            ),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    UnseqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    UnseqEntryAction::Update(value) => {
+                        let _ = update.insert(key, value);
+                    }
+                    UnseqEntryAction::Del => {
+                        let _ = delete.insert(key);
+                    }
+                };
+                (insert, update, delete)
+            },
+        );
+
+        for key in insert.keys() {
+            self.check_key_permissions(Action::Insert, requester, key)?;
+        }
+        for key in update.keys() {
+            self.check_key_permissions(Action::Update, requester, key)?;
+        }
+        for key in &delete {
+            self.check_key_permissions(Action::Delete, requester, key)?;
+        }
+        for key in preconditions.keys() {
+            self.check_key_permissions(Action::Read, requester, key)?;
+        }
+        check_preconditions(&preconditions, |key| {
+            self.data.get(key).map(|value| (&value[..], None))
+        })?;
+
+        let mut new_data = self.data.clone();
+        let mut errors = BTreeMap::new();
+
+        for (key, val) in insert {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => {
+                    let _ = errors.insert(entry.key().clone(), EntryError::EntryExists(0));
+                }
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(val);
+                }
+            }
+        }
+
+        for (key, val) in update {
+            match new_data.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let _ = entry.insert(val);
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
+                }
+            }
+        }
+
+        for key in delete {
+            match new_data.entry(key.clone()) {
+                Entry::Occupied(_) => {
+                    let _ = new_data.remove(&key);
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::InvalidEntryActions(errors));
+        }
+
+        self.replace_entries(new_data, requester, logged_actions.map(Into::into))?;
+
+        Ok(())
+    }
+}
+
+/// Implements functions for sequenced MutableData.
+impl SeqData {
+    /// Creates a new sequenced MutableData.
+    pub fn new(name: XorName, tag: u64, owner: impl Into<OwnerPolicy>) -> Self {
+        Self {
+            address: Address::Seq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        }
+    }
+
+    /// Creates a new sequenced MutableData, readable by anyone.
+    pub fn new_pub(name: XorName, tag: u64, owner: impl Into<OwnerPolicy>) -> Self {
+        Self {
+            address: Address::PubSeq { name, tag },
+            data: Default::default(),
+            permissions: Default::default(),
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        }
+    }
+
+    /// Creates a new sequenced MutableData with entries and permissions.
+    ///
+    /// Returns `Err::TooManyEntries` or `Err::ExceededSize` if the data exceeds the limits.
+    pub fn new_with_data(
+        name: XorName,
+        tag: u64,
+        data: SeqEntries,
+        permissions: BTreeMap<User, PermissionSet>,
+        owner: impl Into<OwnerPolicy>,
+    ) -> Result<Self> {
+        let data = Self {
+            address: Address::Seq { name, tag },
+            data,
+            permissions,
+            scoped_permissions: Default::default(),
+            version: 0,
+            entries_mutations: 0,
+            owner: owner.into(),
+            pending_owner: None,
+            change_log: None,
+        };
+        data.check_size()?;
+        Ok(data)
+    }
+
+    // Checks the data against the limits.
+    fn check_size(&self) -> Result<()> {
+        check_size(&self.data, |value| value.data.len(), self.serialised_size())
+    }
+
+    /// Returns a value by the given key
+    pub fn get(&self, key: &[u8]) -> Option<&SeqValue> {
+        self.data.get(key)
+    }
+
+    /// Returns values of all entries
+    pub fn values(&self) -> Vec<SeqValue> {
+        self.data.values().cloned().collect()
+    }
+
+    /// Returns all entries
+    pub fn entries(&self) -> &SeqEntries {
+        &self.data
+    }
+
+    /// Removes and returns all entries
+    pub fn take_entries(&mut self) -> SeqEntries {
+        mem::replace(&mut self.data, BTreeMap::new())
+    }
+
+    /// Mutates entries (key + value pairs) in bulk. None of the actions are performed unless all
+    /// the preconditions hold, and checking preconditions requires the permission to read. Each
+    /// action is checked against the permissions for its key.
+    ///
+    /// Returns `Err(InvalidEntryActions)` if a precondition does not hold or if the mutation
+    /// parameters are invalid, and `Err(TooManyEntries)` or `Err(ExceededSize)` if the mutated
+    /// data would exceed the limits.
+    pub fn mutate_entries(&mut self, actions: SeqEntryActions, requester: PublicKey) -> Result<()> {
+        let logged_actions = self.change_log.as_ref().map(|_| actions.clone());
+        let SeqEntryActions {
+            actions,
+            preconditions,
+        } = actions;
+        // Deconstruct actions into inserts, updates, and deletes
+        let (insert, update, delete) = actions.into_iter().fold(
+            (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    SeqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    SeqEntryAction::Update(value) => {
+                        let _ = update.insert(key, value);
+                    }
+                    SeqEntryAction::Del(version) => {
+                        let _ = delete.insert(key, version);
+                    }
+                };
+                (insert, update, delete)
+            },
+        );
+
+        for key in insert.keys() {
+            self.check_key_permissions(Action::Insert, requester, key)?;
+        }
+        for key in update.keys() {
+            self.check_key_permissions(Action::Update, requester, key)?;
+        }
+        for key in delete.keys() {
+            self.check_key_permissions(Action::Delete, requester, key)?;
+        }
+        for key in preconditions.keys() {
+            self.check_key_permissions(Action::Read, requester, key)?;
+        }
+        check_preconditions(&preconditions, |key| {
+            self.data
+                .get(key)
+                .map(|value| (&value.data[..], Some(value.version)))
+        })?;
+
+        let mut new_data = self.data.clone();
+        let mut errors = BTreeMap::new();
+
+        for (key, val) in insert {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => {
+                    let _ = errors.insert(
+                        entry.key().clone(),
+                        EntryError::EntryExists(entry.get().version as u8),
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(val);
+                }
+            }
+        }
+
+        for (key, val) in update {
+            match new_data.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let current_version = entry.get().version;
+                    if val.version == current_version + 1 {
+                        let _ = entry.insert(val);
+                    } else {
+                        let _ = errors.insert(
+                            entry.key().clone(),
+                            EntryError::InvalidSuccessor(current_version as u8),
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
+                }
+            }
+        }
+
+        for (key, version) in delete {
+            match new_data.entry(key.clone()) {
+                Entry::Occupied(entry) => {
+                    let current_version = entry.get().version;
+                    if version == current_version + 1 {
+                        let _ = new_data.remove(&key);
+                    } else {
+                        let _ = errors.insert(
+                            entry.key().clone(),
+                            EntryError::InvalidSuccessor(current_version as u8),
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::InvalidEntryActions(errors));
+        }
+
+        self.replace_entries(new_data, requester, logged_actions.map(Into::into))?;
+
+        Ok(())
+    }
+}
+
+/// Kind of a MutableData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Kind {
+    /// Unsequenced.
+    Unseq,
+    /// Sequenced.
+    Seq,
+    /// Unsequenced, readable by anyone.
+    PubUnseq,
+    /// Sequenced, readable by anyone.
+    PubSeq,
+    /// Unsequenced, merging concurrent mutations.
+    Convergent,
+}
+
+impl Kind {
+    /// Creates `Kind` from a `sequenced` flag.
+    pub fn from_flag(sequenced: bool) -> Self {
+        if sequenced {
+            Kind::Seq
+        } else {
+            Kind::Unseq
+        }
+    }
+
+    /// Returns `true` if sequenced.
+    pub fn is_seq(self) -> bool {
+        self == Kind::Seq || self == Kind::PubSeq
+    }
+
+    /// Returns `true` if unsequenced. Convergent data is neither sequenced nor unsequenced.
+    pub fn is_unseq(self) -> bool {
+        self == Kind::Unseq || self == Kind::PubUnseq
+    }
+
+    /// Returns `true` if readable by anyone.
+    pub fn is_pub(self) -> bool {
+        self == Kind::PubUnseq || self == Kind::PubSeq
+    }
+
+    /// Returns `true` if convergent.
+    pub fn is_convergent(self) -> bool {
+        self == Kind::Convergent
+    }
+}
+
+/// Address of an MutableData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Address {
+    /// Unsequenced namespace.
+    Unseq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+    /// Sequenced namespace.
+    Seq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+    /// Unsequenced namespace, readable by anyone.
+    PubUnseq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+    /// Sequenced namespace, readable by anyone.
+    PubSeq {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+    /// Convergent namespace.
+    Convergent {
+        /// Name.
+        name: XorName,
+        /// Tag.
+        tag: u64,
+    },
+}
+
+impl Address {
+    /// Constructs an `Address` given `kind`, `name`, and `tag`.
+    pub fn from_kind(kind: Kind, name: XorName, tag: u64) -> Self {
+        match kind {
+            Kind::Seq => Address::Seq { name, tag },
+            Kind::Unseq => Address::Unseq { name, tag },
+            Kind::PubSeq => Address::PubSeq { name, tag },
+            Kind::PubUnseq => Address::PubUnseq { name, tag },
+            Kind::Convergent => Address::Convergent { name, tag },
+        }
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Address::Seq { .. } => Kind::Seq,
+            Address::Unseq { .. } => Kind::Unseq,
+            Address::PubSeq { .. } => Kind::PubSeq,
+            Address::PubUnseq { .. } => Kind::PubUnseq,
+            Address::Convergent { .. } => Kind::Convergent,
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Address::Unseq { ref name, .. }
+            | Address::Seq { ref name, .. }
+            | Address::PubUnseq { ref name, .. }
+            | Address::PubSeq { ref name, .. }
+            | Address::Convergent { ref name, .. } => name,
+        }
+    }
+
+    /// Returns the tag.
+    pub fn tag(&self) -> u64 {
+        match self {
+            Address::Unseq { tag, .. }
+            | Address::Seq { tag, .. }
+            | Address::PubUnseq { tag, .. }
+            | Address::PubSeq { tag, .. }
+            | Address::Convergent { tag, .. } => *tag,
+        }
+    }
+
+    /// Returns `true` if sequenced.
+    pub fn is_seq(&self) -> bool {
+        self.kind().is_seq()
+    }
+
+    /// Returns `true` if unsequenced.
+    pub fn is_unseq(&self) -> bool {
+        self.kind().is_unseq()
+    }
+
+    /// Returns `true` if readable by anyone.
+    pub fn is_pub(&self) -> bool {
+        self.kind().is_pub()
+    }
+
+    /// Returns `true` if convergent.
+    pub fn is_convergent(&self) -> bool {
+        self.kind().is_convergent()
+    }
+
+    /// Returns the Address serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> String {
+        utils::encode(&self)
+    }
+
+    /// Creates from z-base-32 encoded string.
+    pub fn decode_from_zbase32<T: Decodable>(encoded: T) -> Result<Self> {
+        utils::decode(encoded)
+    }
+}
+
+/// Object storing a MutableData variant.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Data {
+    /// Sequenced MutableData.
+    Seq(SeqData),
+    /// Unsequenced MutableData.
+    Unseq(UnseqData),
+}
+
+impl Data {
+    /// Returns the address of the data.
+    pub fn address(&self) -> &Address {
+        match self {
+            Data::Seq(data) => data.address(),
+            Data::Unseq(data) => data.address(),
+        }
+    }
+
+    /// Returns the kind of the data.
+    pub fn kind(&self) -> Kind {
+        self.address().kind()
+    }
+
+    /// Returns the name of the data.
+    pub fn name(&self) -> &XorName {
+        self.address().name()
+    }
+
+    /// Returns the tag of the data.
+    pub fn tag(&self) -> u64 {
+        self.address().tag()
+    }
+
+    /// Returns true if the data is sequenced.
+    pub fn is_seq(&self) -> bool {
+        self.kind().is_seq()
+    }
+
+    /// Returns true if the data is unsequenced.
+    pub fn is_unseq(&self) -> bool {
+        self.kind().is_unseq()
+    }
+
+    /// Returns true if the data is readable by anyone.
+    pub fn is_pub(&self) -> bool {
+        self.kind().is_pub()
+    }
+
+    /// Returns the version of this data.
+    pub fn version(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.version(),
+            Data::Unseq(data) => data.version(),
+        }
+    }
+
+    /// Returns the number of mutations of this data, of its fields and its entries alike.
+    pub fn mutations(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.mutations(),
+            Data::Unseq(data) => data.mutations(),
+        }
+    }
+
+    /// Returns all the keys in the data.
+    pub fn keys(&self) -> BTreeSet<Vec<u8>> {
+        match self {
+            Data::Seq(data) => data.keys(),
+            Data::Unseq(data) => data.keys(),
+        }
+    }
+
+    /// Returns the values of the given `keys`, or `EntryError::NoSuchEntry` for each missing
+    /// key.
+    pub fn get_values(&self, keys: &BTreeSet<Vec<u8>>) -> ValueResults {
+        keys.iter()
+            .map(|key| {
+                let value = match self {
+                    Data::Seq(data) => data.get(key).cloned().map(Value::from),
+                    Data::Unseq(data) => data.get(key).cloned().map(Value::from),
+                };
+                (key.clone(), value.ok_or(EntryError::NoSuchEntry))
+            })
+            .collect()
+    }
+
+    /// Returns size of this data after serialisation.
+    pub fn serialised_size(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.serialised_size(),
+            Data::Unseq(data) => data.serialised_size(),
+        }
+    }
+
+    /// Returns `true` if the number of entries, the size of each entry and the size of this data
+    /// are within the limits.
+    pub fn validate_size(&self) -> bool {
+        match self {
+            Data::Seq(data) => data.validate_size(),
+            Data::Unseq(data) => data.validate_size(),
+        }
+    }
+
+    /// Returns the change log, if enabled.
+    pub fn change_log(&self) -> Option<&ChangeLog> {
+        match self {
+            Data::Seq(data) => data.change_log(),
+            Data::Unseq(data) => data.change_log(),
+        }
+    }
+
+    /// Returns the changes kept in the change log which were made after the given version of
+    /// the change log.
+    ///
+    /// Returns `Err::InvalidOperation` if the change log is not enabled.
+    pub fn history(&self, since_version: u64) -> Result<Vec<Change>> {
+        self.change_log()
+            .map(|change_log| change_log.since(since_version))
+            .ok_or(Error::InvalidOperation)
+    }
+
+    /// Enables the change log, keeping up to `capacity` changes, or disables it if `capacity` is
+    /// `None`.
+    pub fn set_change_log_capacity(&mut self, capacity: Option<u64>, version: u64) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.set_change_log_capacity(capacity, version),
+            Data::Unseq(data) => data.set_change_log_capacity(capacity, version),
+        }
+    }
+
+    /// Returns a page of the entries within `range`.
+    ///
+    /// Returns `Err::InvalidOperation` if the limit of `range` is zero.
+    pub fn range(&self, range: &KeyRange) -> Result<Page> {
+        match self {
+            Data::Seq(data) => data.range(range),
+            Data::Unseq(data) => data.range(range),
+        }
+    }
+
+    /// Returns the shell of the data.
+    pub fn shell(&self) -> Self {
+        match self {
+            Data::Seq(data) => Data::Seq(data.shell()),
+            Data::Unseq(data) => Data::Unseq(data.shell()),
+        }
+    }
+
+    /// Gets a complete list of permissions.
+    pub fn permissions(&self) -> BTreeMap<User, PermissionSet> {
+        match self {
+            Data::Seq(data) => data.permissions(),
+            Data::Unseq(data) => data.permissions(),
+        }
+    }
+
+    /// Gets the permissions for the provided user.
+    pub fn user_permissions(&self, user: impl Into<User>) -> Result<&PermissionSet> {
+        match self {
+            Data::Seq(data) => data.user_permissions(user),
+            Data::Unseq(data) => data.user_permissions(user),
+        }
+    }
+
+    /// Inserts or update permissions for the provided user.
+    pub fn set_user_permissions(
+        &mut self,
+        user: impl Into<User>,
+        permissions: PermissionSet,
+        version: u64,
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.set_user_permissions(user, permissions, version),
+            Data::Unseq(data) => data.set_user_permissions(user, permissions, version),
+        }
+    }
+
+    /// Deletes permissions for the provided user.
+    pub fn del_user_permissions(&mut self, user: impl Into<User>, version: u64) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.del_user_permissions(user, version),
+            Data::Unseq(data) => data.del_user_permissions(user, version),
+        }
+    }
+
+    /// Gets the permissions scoped to some of the entries for the provided user.
+    pub fn user_scoped_permissions(
+        &self,
+        user: PublicKey,
+    ) -> Result<&BTreeMap<KeyScope, PermissionSet>> {
+        match self {
+            Data::Seq(data) => data.user_scoped_permissions(user),
+            Data::Unseq(data) => data.user_scoped_permissions(user),
+        }
+    }
+
+    /// Inserts or updates permissions scoped to some of the entries for the provided user.
+    pub fn set_scoped_permissions(
+        &mut self,
+        user: PublicKey,
+        scope: KeyScope,
+        permissions: PermissionSet,
+        version: u64,
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.set_scoped_permissions(user, scope, permissions, version),
+            Data::Unseq(data) => data.set_scoped_permissions(user, scope, permissions, version),
+        }
+    }
+
+    /// Deletes permissions scoped to some of the entries for the provided user.
+    pub fn del_scoped_permissions(
+        &mut self,
+        user: PublicKey,
+        scope: &KeyScope,
+        version: u64,
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.del_scoped_permissions(user, scope, version),
+            Data::Unseq(data) => data.del_scoped_permissions(user, scope, version),
+        }
+    }
+
+    /// Checks permissions for given `action` for the provided user.
+    pub fn check_permissions(&self, action: Action, requester: PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_permissions(action, requester),
+            Data::Unseq(data) => data.check_permissions(action, requester),
+        }
+    }
+
+    /// Checks permissions for given `action` on the entry with the given `key` for the provided
+    /// user.
+    pub fn check_key_permissions(
+        &self,
+        action: Action,
+        requester: PublicKey,
+        key: &[u8],
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_key_permissions(action, requester, key),
+            Data::Unseq(data) => data.check_key_permissions(action, requester, key),
+        }
+    }
+
+    /// Checks if the provided user can act as the owner(s) on its own.
+    pub fn check_is_owner(&self, requester: PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_is_owner(requester),
+            Data::Unseq(data) => data.check_is_owner(requester),
+        }
+    }
+
+    /// Checks if `signatures` of `message` are enough to act as the owner(s), either as a set
+    /// of signatures of the owner keys or as a combined threshold signature.
+    pub fn check_is_owner_with_signatures(
+        &self,
+        signatures: &BTreeMap<PublicKey, Signature>,
+        message: &[u8],
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.check_is_owner_with_signatures(signatures, message),
+            Data::Unseq(data) => data.check_is_owner_with_signatures(signatures, message),
+        }
+    }
+
+    /// Returns the owner policy.
+    pub fn owner(&self) -> &OwnerPolicy {
+        match self {
+            Data::Seq(data) => &data.owner,
+            Data::Unseq(data) => &data.owner,
+        }
+    }
+
+    /// Returns the owner(s) the data is being transferred to, if any.
+    pub fn pending_owner(&self) -> Option<&OwnerPolicy> {
+        match self {
+            Data::Seq(data) => data.pending_owner(),
+            Data::Unseq(data) => data.pending_owner(),
+        }
+    }
+
+    /// Changes the owner.
+    pub fn change_owner(&mut self, new_owner: impl Into<OwnerPolicy>, version: u64) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.change_owner(new_owner, version),
+            Data::Unseq(data) => data.change_owner(new_owner, version),
+        }
+    }
+
+    /// Starts transferring the data to `new_owner`, who then has to accept it. Only the owner(s)
+    /// can start a transfer.
+    pub fn propose_owner(
+        &mut self,
+        new_owner: impl Into<OwnerPolicy>,
+        requester: PublicKey,
+        version: u64,
+    ) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.propose_owner(new_owner, requester, version),
+            Data::Unseq(data) => data.propose_owner(new_owner, requester, version),
+        }
+    }
+
+    /// Completes the transfer of the data to the pending owner(s), on behalf of whom `requester`
+    /// accepts it.
+    pub fn accept_owner(&mut self, requester: PublicKey, version: u64) -> Result<()> {
+        match self {
+            Data::Seq(data) => data.accept_owner(requester, version),
+            Data::Unseq(data) => data.accept_owner(requester, version),
+        }
+    }
+
+    /// Mutates entries (key + value pairs) in bulk.
+    pub fn mutate_entries(&mut self, actions: EntryActions, requester: PublicKey) -> Result<()> {
+        match self {
+            Data::Seq(data) => {
+                if let EntryActions::Seq(actions) = actions {
+                    return data.mutate_entries(actions, requester);
+                }
+            }
+            Data::Unseq(data) => {
+                if let EntryActions::Unseq(actions) = actions {
+                    return data.mutate_entries(actions, requester);
+                }
+            }
+        }
+
+        Err(Error::InvalidOperation)
+    }
+}
+
+impl From<SeqData> for Data {
+    fn from(data: SeqData) -> Self {
+        Data::Seq(data)
+    }
+}
+
+impl From<UnseqData> for Data {
+    fn from(data: UnseqData) -> Self {
+        Data::Unseq(data)
+    }
+}
+
+/// Action for a sequenced Entry.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum SeqEntryAction {
+    /// Inserts a new sequenced entry.
+    Ins(SeqValue),
+    /// Updates an entry with a new value and version.
+    Update(SeqValue),
+    /// Deletes an entry.
+    Del(u64),
+}
+
+impl SeqEntryAction {
+    /// Returns the version for this action.
+    pub fn version(&self) -> u64 {
+        match *self {
+            Self::Ins(ref value) => value.version,
+            Self::Update(ref value) => value.version,
+            Self::Del(v) => v,
+        }
+    }
+
+    /// Sets the version for this action.
+    pub fn set_version(&mut self, version: u64) {
+        match *self {
+            Self::Ins(ref mut value) => value.version = version,
+            Self::Update(ref mut value) => value.version = version,
+            Self::Del(ref mut v) => *v = version,
+        }
+    }
+}
+
+/// Action for an unsequenced Entry.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum UnseqEntryAction {
+    /// Inserts a new unsequenced entry.
+    Ins(Vec<u8>),
+    /// Updates an entry with a new value.
+    Update(Vec<u8>),
+    /// Deletes an entry.
+    Del,
+}
+
+/// Condition on the current state of an entry, checked before any entry action is applied.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum Precondition {
+    /// The entry must not exist.
+    Absent,
+    /// The entry must exist.
+    Present,
+    /// The entry must exist with the given version. Never holds for unsequenced entries.
+    Version(u64),
+    /// The entry must exist with the given value.
+    Value(Vec<u8>),
+}
+
+impl Precondition {
+    // Checks the precondition against the current value of an entry, along with its version if
+    // the entry is sequenced. `entry` is `None` if the entry does not exist.
+    fn check(&self, entry: Option<(&[u8], Option<u64>)>) -> Option<EntryError> {
+        let current_version = |version: Option<u64>| version.unwrap_or(0) as u8;
+        match (self, entry) {
+            (Precondition::Absent, None) => None,
+            (Precondition::Absent, Some((_, version))) => {
+                Some(EntryError::EntryExists(current_version(version)))
+            }
+            (_, None) => Some(EntryError::NoSuchEntry),
+            (Precondition::Present, Some(_)) => None,
+            (Precondition::Version(expected), Some((_, version))) if version == Some(*expected) => {
+                None
+            }
+            (Precondition::Value(expected), Some((value, _))) if value == &expected[..] => None,
+            (_, Some((_, version))) => {
+                Some(EntryError::PreconditionFailed(current_version(version)))
+            }
+        }
+    }
+}
+
+// Checks all the `preconditions`, looking the current state of the entries up with `entry`.
+fn check_preconditions<'a>(
+    preconditions: &BTreeMap<Vec<u8>, Precondition>,
+    entry: impl Fn(&[u8]) -> Option<(&'a [u8], Option<u64>)>,
+) -> Result<()> {
+    let errors: BTreeMap<_, _> = preconditions
+        .iter()
+        .filter_map(|(key, precondition)| {
+            precondition
+                .check(entry(key))
+                .map(|error| (key.clone(), error))
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidEntryActions(errors))
+    }
+}
+
+/// Sequenced Entry Actions for given entry keys.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct SeqEntryActions {
+    // A map containing keys and corresponding sequenced entry actions to perform.
+    actions: BTreeMap<Vec<u8>, SeqEntryAction>,
+    // A map containing keys and the conditions their entries must meet for any action to be
+    // performed.
+    preconditions: BTreeMap<Vec<u8>, Precondition>,
+}
+
+impl SeqEntryActions {
+    /// Creates a new sequenced Entry Actions list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Gets the actions.
+    pub fn actions(&self) -> &BTreeMap<Vec<u8>, SeqEntryAction> {
+        &self.actions
+    }
+
+    /// Converts `self` to a map of the keys with their corresponding action.
+    pub fn into_actions(self) -> BTreeMap<Vec<u8>, SeqEntryAction> {
+        self.actions
+    }
+
+    /// Inserts a new key-value pair.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn ins(mut self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(
+            key,
+            SeqEntryAction::Ins(SeqValue {
+                data: content,
+                version,
+            }),
+        );
+        self
+    }
+
+    /// Updates an existing key-value pair.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn update(mut self, key: Vec<u8>, content: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(
+            key,
+            SeqEntryAction::Update(SeqValue {
+                data: content,
+                version,
+            }),
+        );
+        self
+    }
+
+    /// Deletes an entry.
+    ///
+    /// Requires the new `version` of the sequenced entry content. If it does not match the current
+    /// version + 1, an error will be returned.
+    pub fn del(mut self, key: Vec<u8>, version: u64) -> Self {
+        let _ = self.actions.insert(key, SeqEntryAction::Del(version));
+        self
+    }
+
+    /// Adds an action to the list of actions, replacing it if it is already present.
+    pub fn add_action(&mut self, key: Vec<u8>, action: SeqEntryAction) {
+        let _ = self.actions.insert(key, action);
+    }
+
+    /// Gets the preconditions.
+    pub fn preconditions(&self) -> &BTreeMap<Vec<u8>, Precondition> {
+        &self.preconditions
+    }
+
+    /// Requires the entry under `key` to meet `precondition`, replacing any precondition already
+    /// present for it. The actions are only performed if all the preconditions hold.
+    pub fn require(mut self, key: Vec<u8>, precondition: Precondition) -> Self {
+        let _ = self.preconditions.insert(key, precondition);
+        self
+    }
+}
+
+impl From<SeqEntryActions> for BTreeMap<Vec<u8>, SeqEntryAction> {
+    fn from(actions: SeqEntryActions) -> Self {
+        actions.actions
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, SeqEntryAction>> for SeqEntryActions {
+    fn from(actions: BTreeMap<Vec<u8>, SeqEntryAction>) -> Self {
+        SeqEntryActions {
+            actions,
+            preconditions: Default::default(),
+        }
+    }
+}
+
+/// Unsequenced Entry Actions for given entry keys.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct UnseqEntryActions {
+    // A BTreeMap containing keys to which the corresponding unsequenced entry action is to be
+    // performed.
+    actions: BTreeMap<Vec<u8>, UnseqEntryAction>,
+    // A map containing keys and the conditions their entries must meet for any action to be
+    // performed.
+    preconditions: BTreeMap<Vec<u8>, Precondition>,
+}
+
+impl UnseqEntryActions {
+    /// Creates a new unsequenced Entry Actions list.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Gets the actions.
+    pub fn actions(&self) -> &BTreeMap<Vec<u8>, UnseqEntryAction> {
+        &self.actions
+    }
+
+    /// Converts UnseqEntryActions struct to a BTreeMap of the keys with their corresponding action.
+    pub fn into_actions(self) -> BTreeMap<Vec<u8>, UnseqEntryAction> {
+        self.actions
+    }
+
+    /// Insert a new key-value pair
+    pub fn ins(mut self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Ins(content));
+        self
+    }
+
+    /// Update existing key-value pair
+    pub fn update(mut self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Update(content));
+        self
+    }
+
+    /// Delete existing key
+    pub fn del(mut self, key: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Del);
+        self
+    }
+
+    /// Adds a UnseqEntryAction to the list of actions, replacing it if it is already present
+    pub fn add_action(&mut self, key: Vec<u8>, action: UnseqEntryAction) {
+        let _ = self.actions.insert(key, action);
+    }
+
+    /// Gets the preconditions.
+    pub fn preconditions(&self) -> &BTreeMap<Vec<u8>, Precondition> {
+        &self.preconditions
+    }
+
+    /// Requires the entry under `key` to meet `precondition`, replacing any precondition already
+    /// present for it. The actions are only performed if all the preconditions hold.
+    pub fn require(mut self, key: Vec<u8>, precondition: Precondition) -> Self {
+        let _ = self.preconditions.insert(key, precondition);
+        self
+    }
+}
+
+impl From<UnseqEntryActions> for BTreeMap<Vec<u8>, UnseqEntryAction> {
+    fn from(actions: UnseqEntryActions) -> Self {
+        actions.actions
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, UnseqEntryAction>> for UnseqEntryActions {
+    fn from(actions: BTreeMap<Vec<u8>, UnseqEntryAction>) -> Self {
+        UnseqEntryActions {
+            actions,
+            preconditions: Default::default(),
+        }
+    }
+}
+
+/// Wrapper type for entry actions, which can be sequenced or unsequenced.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum EntryActions {
+    /// Sequenced entry actions.
+    Seq(SeqEntryActions),
+    /// Unsequenced entry actions.
+    Unseq(UnseqEntryActions),
+}
+
+impl EntryActions {
+    /// Gets the kind.
+    pub fn kind(&self) -> Kind {
+        match self {
+            EntryActions::Seq(_) => Kind::Seq,
+            EntryActions::Unseq(_) => Kind::Unseq,
+        }
+    }
+}
+
+impl From<SeqEntryActions> for EntryActions {
+    fn from(entry_actions: SeqEntryActions) -> Self {
+        EntryActions::Seq(entry_actions)
+    }
+}
+
+impl From<UnseqEntryActions> for EntryActions {
+    fn from(entry_actions: UnseqEntryActions) -> Self {
+        EntryActions::Unseq(entry_actions)
+    }
+}
+
+/// Sequenced entries (key-value pairs, with versioned values).
+pub type SeqEntries = BTreeMap<Vec<u8>, SeqValue>;
+/// Unsequenced entries (key-value pairs, without versioned values).
+pub type UnseqEntries = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Wrapper type for entries, which can be sequenced or unsequenced.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum Entries {
+    /// Sequenced entries.
+    Seq(SeqEntries),
+    /// Unsequenced entries.
+    Unseq(UnseqEntries),
+}
+
+impl From<SeqEntries> for Entries {
+    fn from(entries: SeqEntries) -> Self {
+        Entries::Seq(entries)
+    }
+}
+
+impl From<UnseqEntries> for Entries {
+    fn from(entries: UnseqEntries) -> Self {
+        Entries::Unseq(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Action, Address, ConvergentData, Data, Entries, EntryActions, KeyRange, KeyScope,
+        PermissionSet, Precondition, SeqData, SeqEntryActions, SeqValue, TypedSeqData,
+        TypedUnseqData, UnseqData, UnseqEntryActions, User, Value, XorName,
+        MAX_MUTABLE_DATA_ENTRIES, MAX_MUTABLE_DATA_KEY_SIZE_IN_BYTES,
+        MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES,
+    };
+    use crate::{
+        EntryError, Error, Keypair, MDataRequest, OwnerPolicy, PublicKey,
+        RequestAuthKind as AuthorisationKind, RequestType, Response, Result,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+    use threshold_crypto::SecretKey;
+    use unwrap::unwrap;
+
+    #[test]
+    fn zbase32_encode_decode_mdata_address() {
+        let name = XorName(rand::random());
+        let address = Address::Seq { name, tag: 15000 };
+        let encoded = address.encode_to_zbase32();
+        let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
+        assert_eq!(address, decoded);
+    }
+
+    #[test]
+    fn expiring_permissions() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName(rand::random()), 15000, owner);
+
+        let permissions = PermissionSet::new()
+            .allow(Action::Insert)
+            .valid_from(2)
+            .valid_until(2);
+        data.set_user_permissions(user, permissions, 1)?;
+
+        // not in effect yet
+        assert_eq!(
+            data.check_permissions(Action::Insert, user),
+            Err(Error::AccessDenied)
+        );
+
+        data.set_user_permissions(owner, PermissionSet::new(), 2)?;
+        let actions = UnseqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec());
+        data.mutate_entries(actions, user)?;
+
+        // expired
+        data.set_user_permissions(owner, PermissionSet::new(), 3)?;
+        assert!(!data.is_action_allowed(&user, Action::Insert));
+        let actions = UnseqEntryActions::new().ins(b"other".to_vec(), b"value".to_vec());
+        assert_eq!(
+            data.mutate_entries(actions, user),
+            Err(Error::PermissionExpired)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn entry_mutations_expire_permissions() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName(rand::random()), 15000, owner);
+
+        let permissions = PermissionSet::new().allow(Action::Insert).valid_until(2);
+        data.set_user_permissions(user, permissions, 1)?;
+        assert_eq!(data.mutations(), 1);
+
+        let actions = UnseqEntryActions::new().ins(b"key1".to_vec(), b"value".to_vec());
+        data.mutate_entries(actions, user)?;
+        let actions = UnseqEntryActions::new().ins(b"key2".to_vec(), b"value".to_vec());
+        data.mutate_entries(actions, user)?;
+        assert_eq!(data.version(), 1);
+        assert_eq!(data.mutations(), 3);
+
+        // expired by the entry mutations alone
+        let actions = UnseqEntryActions::new().ins(b"key3".to_vec(), b"value".to_vec());
+        assert_eq!(
+            data.mutate_entries(actions, user),
+            Err(Error::PermissionExpired)
+        );
+        assert_eq!(data.mutations(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_mutations() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut unseq = UnseqData::new(XorName(rand::random()), 15000, owner);
+        unseq.mutate_entries(
+            UnseqEntryActions::new().ins(b"stock".to_vec(), b"1".to_vec()),
+            owner,
+        )?;
+
+        // a stale read fails without applying any action
+        let actions = UnseqEntryActions::new()
+            .update(b"stock".to_vec(), b"0".to_vec())
+            .ins(b"order".to_vec(), b"1".to_vec())
+            .require(b"stock".to_vec(), Precondition::Value(b"2".to_vec()))
+            .require(b"order".to_vec(), Precondition::Absent);
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"stock".to_vec(), EntryError::PreconditionFailed(0));
+        assert_eq!(
+            unseq.mutate_entries(actions, owner),
+            Err(Error::InvalidEntryActions(errors))
+        );
+        assert_eq!(unseq.get(b"stock"), Some(&b"1".to_vec()));
+        assert_eq!(unseq.get(b"order"), None);
+
+        let actions = UnseqEntryActions::new()
+            .update(b"stock".to_vec(), b"0".to_vec())
+            .ins(b"order".to_vec(), b"1".to_vec())
+            .require(b"stock".to_vec(), Precondition::Value(b"1".to_vec()))
+            .require(b"order".to_vec(), Precondition::Absent);
+        unseq.mutate_entries(actions, owner)?;
+        assert_eq!(unseq.get(b"stock"), Some(&b"0".to_vec()));
+
+        let mut seq = SeqData::new(XorName(rand::random()), 15000, owner);
+        seq.mutate_entries(
+            SeqEntryActions::new()
+                .ins(b"a".to_vec(), b"a".to_vec(), 0)
+                .ins(b"b".to_vec(), b"b".to_vec(), 0),
+            owner,
+        )?;
+
+        let actions = SeqEntryActions::new()
+            .update(b"b".to_vec(), b"b".to_vec(), 1)
+            .require(b"a".to_vec(), Precondition::Version(1))
+            .require(b"c".to_vec(), Precondition::Present);
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"a".to_vec(), EntryError::PreconditionFailed(0));
+        let _ = errors.insert(b"c".to_vec(), EntryError::NoSuchEntry);
+        assert_eq!(
+            seq.mutate_entries(actions, owner),
+            Err(Error::InvalidEntryActions(errors))
+        );
+
+        let actions = SeqEntryActions::new()
+            .update(b"b".to_vec(), b"b".to_vec(), 1)
+            .require(b"a".to_vec(), Precondition::Version(0));
+        seq.mutate_entries(actions, owner)
+    }
+
+    #[test]
+    fn range_queries() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName(rand::random()), 15000, owner);
+        let keys: Vec<Vec<u8>> = vec![
+            b"a".to_vec(),
+            b"b/1".to_vec(),
+            b"b/2".to_vec(),
+            b"b/3".to_vec(),
+            b"c".to_vec(),
+        ];
+        let actions = keys.iter().fold(UnseqEntryActions::new(), |actions, key| {
+            actions.ins(key.clone(), key.clone())
+        });
+        data.mutate_entries(actions, owner)?;
+
+        let page_keys = |page: &super::Page| match &page.entries {
+            Entries::Unseq(entries) => entries.keys().cloned().collect::<Vec<_>>(),
+            Entries::Seq(_) => panic!("unexpected sequenced entries"),
+        };
+
+        let page = data.range(&KeyRange::new(b"b".to_vec(), b"c".to_vec()))?;
+        assert_eq!(page_keys(&page), keys[1..4].to_vec());
+        assert_eq!(page.continuation, None);
+
+        // prefix, in pages of two
+        let range = KeyRange::prefix(b"b/".to_vec()).limit(2);
+        let page = data.range(&range)?;
+        assert_eq!(page_keys(&page), keys[1..3].to_vec());
+        let continuation = unwrap!(page.continuation);
+        let page = data.range(&range.clone().continue_from(continuation))?;
+        assert_eq!(page_keys(&page), keys[3..4].to_vec());
+        assert_eq!(page.continuation, None);
+
+        // reverse
+        let range = KeyRange::new(None, None).limit(2).reverse();
+        let page = data.range(&range)?;
+        assert_eq!(page_keys(&page), keys[3..5].to_vec());
+        let page = data.range(&range.continue_from(unwrap!(page.continuation)))?;
+        assert_eq!(page_keys(&page), keys[1..3].to_vec());
+
+        // empty ranges
+        assert!(page_keys(&data.range(&KeyRange::new(b"c".to_vec(), b"a".to_vec()))?).is_empty());
+        assert_eq!(KeyRange::prefix(vec![0xff]).end, None);
+        assert_eq!(
+            data.range(&KeyRange::new(None, None).limit(0)),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(KeyRange::prefix(vec![1, 0xff]).end, Some(vec![2]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_values() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = SeqData::new(XorName(rand::random()), 15000, owner);
+        data.mutate_entries(
+            SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0),
+            owner,
+        )?;
+        let data = Data::Seq(data);
+
+        let keys: BTreeSet<_> = vec![b"key".to_vec(), b"missing".to_vec()]
+            .into_iter()
+            .collect();
+        let values = data.get_values(&keys);
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            values[&b"key".to_vec()],
+            Ok(Value::Seq(SeqValue {
+                data: b"value".to_vec(),
+                version: 0,
+            }))
+        );
+        assert_eq!(values[&b"missing".to_vec()], Err(EntryError::NoSuchEntry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn size_limits() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let name = XorName(rand::random());
+
+        let entries: BTreeMap<_, _> = (0..=MAX_MUTABLE_DATA_ENTRIES)
+            .map(|i| (i.to_be_bytes().to_vec(), vec![]))
+            .collect();
+        assert_eq!(
+            UnseqData::new_with_data(name, 15000, entries, BTreeMap::new(), owner),
+            Err(Error::TooManyEntries)
+        );
+
+        let mut data = UnseqData::new(name, 15000, owner);
+        let key = vec![0; MAX_MUTABLE_DATA_KEY_SIZE_IN_BYTES as usize + 1];
+        let actions = UnseqEntryActions::new().ins(key, vec![]);
+        assert_eq!(
+            data.mutate_entries(actions, owner),
+            Err(Error::ExceededSize)
+        );
+
+        // none of the entries are inserted if the result exceeds the limits
+        let value = vec![0; MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES as usize];
+        let actions = (0..11u8).fold(UnseqEntryActions::new(), |actions, i| {
+            actions.ins(vec![i], value.clone())
+        });
+        assert_eq!(
+            data.mutate_entries(actions, owner),
+            Err(Error::ExceededSize)
+        );
+        assert!(data.entries().is_empty());
+        assert!(data.validate_size());
+
+        Ok(())
+    }
+
+    #[test]
+    fn scoped_permissions() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let app = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName(rand::random()), 15000, owner);
+
+        let scope = KeyScope::Prefix(b"photos/".to_vec());
+        let permissions = PermissionSet::new()
+            .allow(Action::Insert)
+            .allow(Action::Update);
+        data.set_scoped_permissions(app, scope.clone(), permissions, 1)?;
+        assert_eq!(data.user_scoped_permissions(app)?.len(), 1);
+
+        let actions = UnseqEntryActions::new().ins(b"photos/1".to_vec(), vec![]);
+        data.mutate_entries(actions, app)?;
+
+        // nothing is mutated if any key is out of scope
+        let actions = UnseqEntryActions::new()
+            .update(b"photos/1".to_vec(), vec![1])
+            .ins(b"docs/1".to_vec(), vec![]);
+        assert_eq!(data.mutate_entries(actions, app), Err(Error::AccessDenied));
+        assert_eq!(data.get(b"photos/1"), Some(&vec![]));
+        assert_eq!(
+            data.check_key_permissions(Action::Delete, app, b"photos/1"),
+            Err(Error::AccessDenied)
+        );
+
+        data.del_scoped_permissions(app, &scope, 2)?;
+        assert_eq!(data.user_scoped_permissions(app), Err(Error::NoSuchKey));
+        assert_eq!(
+            data.check_key_permissions(Action::Insert, app, b"photos/2"),
+            Err(Error::AccessDenied)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn public_data_and_anyone_permissions() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = SeqData::new_pub(XorName(rand::random()), 15000, owner);
+        assert!(data.address().is_pub() && data.address().is_seq());
+
+        // anyone can read public data
+        data.check_permissions(Action::Read, user)?;
+        assert_eq!(
+            data.check_permissions(Action::Insert, user),
+            Err(Error::AccessDenied)
+        );
+        assert_eq!(
+            MDataRequest::Get(*data.address()).get_type(),
+            RequestType::PublicGet
+        );
+
+        data.set_user_permissions(User::Anyone, PermissionSet::new().allow(Action::Insert), 1)?;
+        data.mutate_entries(
+            SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0),
+            user,
+        )?;
+
+        let data = UnseqData::new(XorName(rand::random()), 15000, owner);
+        assert_eq!(
+            data.check_permissions(Action::Read, user),
+            Err(Error::AccessDenied)
+        );
+        assert_eq!(
+            MDataRequest::Get(*data.address()).get_type(),
+            RequestType::PrivateGet
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn change_log() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = Data::Seq(SeqData::new(XorName(rand::random()), 15000, owner));
+        assert_eq!(data.history(0), Err(Error::InvalidOperation));
+
+        data.set_change_log_capacity(Some(2), 1)?;
+        data.set_user_permissions(user, PermissionSet::new().allow(Action::Insert), 2)?;
+        let actions: Vec<EntryActions> = (0..3u8)
+            .map(|i| SeqEntryActions::new().ins(vec![i], vec![i], 0).into())
+            .collect();
+        data.mutate_entries(actions[0].clone(), owner)?;
+        data.mutate_entries(actions[1].clone(), user)?;
+        data.mutate_entries(actions[2].clone(), user)?;
+
+        // only the latest changes are kept
+        let history = data.history(0)?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, 2);
+        assert_eq!(history[0].requester, user);
+        assert_eq!(history[0].actions, actions[1]);
+        assert_eq!(history[0].entry_versions.get(&vec![1]), Some(&0));
+        assert_eq!(data.history(2)?.len(), 1);
+
+        // failed mutations are not recorded
+        assert!(data.mutate_entries(actions[2].clone(), user).is_err());
+        assert_eq!(unwrap!(data.change_log()).version(), 3);
+
+        data.set_change_log_capacity(None, 3)?;
+        assert!(data.change_log().is_none());
+        assert_eq!(
+            data.set_change_log_capacity(Some(MAX_MUTABLE_DATA_ENTRIES + 1), 4),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn change_log_size() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqData::new(XorName(rand::random()), 15000, owner);
+        data.set_change_log_capacity(Some(MAX_MUTABLE_DATA_ENTRIES), 1)?;
+
+        // the change log counts towards the size of the data
+        let value = vec![0; MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES as usize];
+        let result = loop {
+            let actions = if data.get(&[0]).is_none() {
+                UnseqEntryActions::new().ins(vec![0], value.clone())
+            } else {
+                UnseqEntryActions::new().update(vec![0], value.clone())
+            };
+            let version = unwrap!(data.change_log()).version();
+            if let Err(error) = data.mutate_entries(actions, owner) {
+                assert_eq!(unwrap!(data.change_log()).version(), version);
+                break error;
+            }
+        };
+        assert_eq!(result, Error::ExceededSize);
+        assert!(data.validate_size());
+
+        Ok(())
+    }
+
+    #[test]
+    fn convergent_data() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut replica1 = ConvergentData::new(XorName(rand::random()), 15000, owner);
+        assert!(replica1.address().is_convergent());
+        assert!(!replica1.address().is_seq());
+        assert!(!replica1.address().is_unseq());
+
+        let permissions = PermissionSet::new()
+            .allow(Action::Insert)
+            .allow(Action::Update);
+        let op1 = replica1.set_user_permissions(user, permissions, owner)?;
+        let mut replica2 = replica1.clone();
+
+        // concurrent writes to the same key, and a deletion
+        let op2 = replica1.set(b"key".to_vec(), b"owner".to_vec(), owner)?;
+        let op3 = replica1.set(b"other".to_vec(), b"value".to_vec(), owner)?;
+        let op4 = replica2.set(b"key".to_vec(), b"user".to_vec(), user)?;
+        assert_eq!(
+            replica2.del(b"other".to_vec(), user),
+            Err(Error::NoSuchEntry)
+        );
+        let op5 = replica1.del(b"other".to_vec(), owner)?;
+
+        // replicas converge whatever the order the operations are applied in
+        let mut replica3 = ConvergentData::new(*replica1.name(), 15000, owner);
+        for op in [op5.clone(), op4.clone(), op3, op2.clone(), op1] {
+            replica3.apply_op(op)?;
+        }
+        replica1.apply_op(op4)?;
+        replica2.apply_op(op2)?;
+        replica2.apply_op(op5)?;
+        assert_eq!(replica1, replica3);
+        assert_eq!(replica2, replica3);
+        assert!(replica1.get(b"other").is_none());
+        assert_eq!(replica1.keys().len(), 1);
+
+        // operations are only applied by allowed actors
+        let mut replica4 = replica1.clone();
+        let op = replica4.set(b"key".to_vec(), b"value".to_vec(), user)?;
+        assert_eq!(
+            replica1.apply_checked_op(op.clone(), owner),
+            Err(Error::InvalidOperation)
+        );
+        replica1.apply_checked_op(op, user)?;
+        assert_eq!(replica1.get(b"key"), Some(&b"value".to_vec()));
+        let op = replica4.del(b"key".to_vec(), owner)?;
+        let _ = replica1.del_user_permissions(user, owner)?;
+        assert_eq!(
+            replica1.set(b"key".to_vec(), b"other".to_vec(), user),
+            Err(Error::AccessDenied)
+        );
+        replica1.apply_checked_op(op, owner)?;
+        assert!(replica1.entries().is_empty());
+
+        let request = MDataRequest::ApplyConvergentOp(replica4.set(
+            b"key".to_vec(),
+            b"value".to_vec(),
+            owner,
+        )?);
+        assert_eq!(request.get_type(), RequestType::Mutation);
+        assert_eq!(
+            MDataRequest::GetConvergent(*replica4.address()).get_type(),
+            RequestType::PrivateGet
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn convergent_data_stamps() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let mut replica1 = ConvergentData::new(XorName(rand::random()), 15000, owner);
+        let permissions = PermissionSet::new().allow(Action::Insert);
+        let _ = replica1.set_user_permissions(user, permissions, owner)?;
+        let mut replica2 = replica1.clone();
+
+        // an operation can't jump the clock ahead
+        let mut op = replica2.set(b"key".to_vec(), b"value".to_vec(), user)?;
+        op.crdt_op.stamp.counter = u64::MAX;
+        assert_eq!(
+            replica1.apply_checked_op(op, user),
+            Err(Error::InvalidSuccessor(1))
+        );
+        let _ = replica1.set(b"other".to_vec(), b"value".to_vec(), owner)?;
+
+        // an operation is checked against the current permissions, so once revoked, a user
+        // can't get an operation accepted, even if issued before the revocation or backdated
+        let op = replica2.set(b"third".to_vec(), b"value".to_vec(), user)?;
+        let _ = replica1.del(b"other".to_vec(), owner)?;
+        let revocation = replica1.del_user_permissions(user, owner)?;
+        let mut replica3 = replica1.clone();
+        assert_eq!(
+            replica1.apply_checked_op(op.clone(), user),
+            Err(Error::AccessDenied)
+        );
+        let mut backdated = op.clone();
+        backdated.crdt_op.stamp.counter = 1;
+        assert_eq!(
+            replica1.apply_checked_op(backdated, user),
+            Err(Error::AccessDenied)
+        );
+
+        // an operation accepted by a replica is applied as it is by the others
+        replica1.apply_op(op.clone())?;
+        replica2.apply_checked_op(revocation, owner)?;
+        assert_eq!(replica1.get(b"third"), Some(&b"value".to_vec()));
+        assert_eq!(
+            replica2.set(b"fourth".to_vec(), b"value".to_vec(), user),
+            Err(Error::AccessDenied)
+        );
+
+        // a clock which can't advance anymore rejects further local operations
+        let mut exhausted = op;
+        exhausted.crdt_op.stamp.counter = u64::MAX;
+        replica3.apply_op(exhausted)?;
+        assert_eq!(
+            replica3.set(b"key".to_vec(), b"value".to_vec(), owner),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn convergent_data_limits() -> Result<()> {
+        // An Ed25519 owner keeps the stamps cheap to serialise when checking the size.
+        let owner = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let mut data = ConvergentData::new(XorName(rand::random()), 15000, owner);
+
+        // tombstones count towards the number of entries
+        for i in 0..MAX_MUTABLE_DATA_ENTRIES {
+            let _ = data.set(i.to_be_bytes().to_vec(), vec![], owner)?;
+        }
+        let _ = data.del(0u64.to_be_bytes().to_vec(), owner)?;
+        assert_eq!(
+            data.set(b"key".to_vec(), vec![], owner),
+            Err(Error::TooManyEntries)
+        );
+        let _ = data.set(0u64.to_be_bytes().to_vec(), vec![], owner)?;
+
+        // the size of the data is limited as well
+        let mut data = ConvergentData::new(XorName(rand::random()), 15000, owner);
+        let value = vec![0; MAX_MUTABLE_DATA_VALUE_SIZE_IN_BYTES as usize];
+        let result = (0u64..)
+            .map(|key| data.set(key.to_be_bytes().to_vec(), value.clone(), owner))
+            .find_map(Result::err);
+        assert_eq!(result, Some(Error::ExceededSize));
+        assert!(data.validate_size());
+
+        Ok(())
+    }
+
+    #[test]
+    fn owner_transfer() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let new_owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data: Data = SeqData::new(XorName(rand::random()), 15000, owner).into();
+
+        assert_eq!(
+            data.accept_owner(new_owner, 1),
+            Err(Error::InvalidOperation)
+        );
+        // only the owner can propose
+        assert_eq!(
+            data.propose_owner(new_owner, new_owner, 1),
+            Err(Error::AccessDenied)
+        );
+        data.propose_owner(new_owner, owner, 1)?;
+        assert_eq!(data.owner(), &OwnerPolicy::Key(owner));
+        assert_eq!(data.pending_owner(), Some(&OwnerPolicy::Key(new_owner)));
+
+        // only the pending owner can accept
+        assert_eq!(data.accept_owner(owner, 2), Err(Error::AccessDenied));
+        assert_eq!(
+            data.accept_owner(new_owner, 3),
+            Err(Error::InvalidSuccessor(1))
+        );
+        data.accept_owner(new_owner, 2)?;
+        assert_eq!(data.owner(), &OwnerPolicy::Key(new_owner));
+        assert!(data.pending_owner().is_none());
+        assert!(data.check_is_owner(new_owner).is_ok());
+
+        let request = MDataRequest::ChangeOwner {
+            address: *data.address(),
+            new_owner: owner.into(),
+            version: 3,
+        };
+        assert_eq!(request.get_type(), RequestType::Mutation);
+        assert_eq!(
+            request.error_response(Error::AccessDenied),
+            Response::Mutation(Err(Error::AccessDenied))
+        );
+        let request = MDataRequest::AcceptOwner {
+            address: *data.address(),
+            version: 3,
+        };
+        assert!(matches!(
+            request.authorisation_kind(),
+            AuthorisationKind::PendingOwnerMutation
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_entries() -> Result<()> {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = SeqData::new(XorName(rand::random()), 15000, owner);
+
+        let mut typed = TypedSeqData::<String, u32>::new(&data);
+        let _ = typed.insert(&"apples".to_string(), &3)?;
+        let _ = typed.insert(&"pears".to_string(), &5)?;
+        data.mutate_entries(typed.into_actions(), owner)?;
+
+        let mut typed = TypedSeqData::<String, u32>::new(&data);
+        assert_eq!(typed.get(&"apples".to_string())?, Some((3, 0)));
+        assert!(typed.insert(&"apples".to_string(), &1).is_err());
+        assert_eq!(
+            typed.update(&"plums".to_string(), &1).map(|_| ()),
+            Err(Error::NoSuchEntry)
+        );
+        let _ = typed
+            .update(&"apples".to_string(), &4)?
+            .delete(&"pears".to_string())?;
+        data.mutate_entries(typed.into_actions(), owner)?;
+
+        let typed = TypedSeqData::<String, u32>::new(&data);
+        let entries = typed.entries()?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries.get("apples"), Some(&(4, 1)));
+
+        // values of another type fail to decode
+        let typed = TypedSeqData::<String, String>::new(&data);
+        assert!(matches!(
+            typed.get(&"apples".to_string()),
+            Err(Error::FailedToParse(_))
+        ));
+
+        let mut unseq = UnseqData::new(XorName(rand::random()), 15000, owner);
+        let mut typed = TypedUnseqData::<u64, Vec<String>>::new(&unseq);
+        let _ = typed.insert(&1, &vec!["a".to_string()])?;
+        unseq.mutate_entries(typed.into_actions(), owner)?;
+        let mut typed = TypedUnseqData::<u64, Vec<String>>::new(&unseq);
+        assert_eq!(typed.get(&1)?, Some(vec!["a".to_string()]));
+        let _ = typed.delete(&1)?;
+        unseq.mutate_entries(typed.into_actions(), owner)?;
+        assert!(unseq.entries().is_empty());
+
+        Ok(())
+    }
+}
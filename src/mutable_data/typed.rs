@@ -0,0 +1,229 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Typed views of MutableData entries.
+//!
+//! A typed view reads the entries of a `SeqData` or `UnseqData` as keys and values of given
+//! serde types, and builds the entry actions mutating them. Keys and values are encoded with
+//! bincode, and entries which fail to decode are reported as `Error::FailedToParse`.
+
+use super::{
+    SeqData, SeqEntryAction, SeqEntryActions, SeqValue, UnseqData, UnseqEntryAction,
+    UnseqEntryActions,
+};
+use crate::{utils, EntryError, Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeMap, iter, marker::PhantomData};
+
+/// Typed view of the entries of a sequenced MutableData, with keys of type `K` and values of type
+/// `V`.
+///
+/// Mutations are accumulated into `SeqEntryActions`, with the entry versions following the ones
+/// of the viewed data.
+pub struct TypedSeqData<'a, K, V> {
+    data: &'a SeqData,
+    actions: SeqEntryActions,
+    _types: PhantomData<fn(K, V)>,
+}
+
+impl<'a, K, V> TypedSeqData<'a, K, V>
+where
+    K: Serialize + DeserializeOwned + Ord,
+    V: Serialize + DeserializeOwned,
+{
+    /// Creates a typed view of `data`.
+    pub fn new(data: &'a SeqData) -> Self {
+        Self {
+            data,
+            actions: SeqEntryActions::new(),
+            _types: PhantomData,
+        }
+    }
+
+    /// Returns the value of the entry at `key` and its version, if it exists.
+    pub fn get(&self, key: &K) -> Result<Option<(V, u64)>> {
+        self.data
+            .get(&utils::serialise(key))
+            .map(|value| Ok((utils::deserialise(&value.data)?, value.version)))
+            .transpose()
+    }
+
+    /// Returns all the entries, with their versions.
+    pub fn entries(&self) -> Result<BTreeMap<K, (V, u64)>> {
+        self.data
+            .entries()
+            .iter()
+            .map(|(key, value)| {
+                Ok((
+                    utils::deserialise(key)?,
+                    (utils::deserialise(&value.data)?, value.version),
+                ))
+            })
+            .collect()
+    }
+
+    /// Adds an action inserting a new entry.
+    ///
+    /// Returns `Err(Error::InvalidEntryActions(_))` holding `EntryError::EntryExists` if the entry
+    /// already exists.
+    pub fn insert(&mut self, key: &K, value: &V) -> Result<&mut Self> {
+        let key = utils::serialise(key);
+        if let Some(current) = self.data.get(&key) {
+            return Err(entry_exists(key, current.version));
+        }
+        self.actions.add_action(
+            key,
+            SeqEntryAction::Ins(SeqValue {
+                data: utils::serialise(value),
+                version: 0,
+            }),
+        );
+        Ok(self)
+    }
+
+    /// Adds an action updating an existing entry to its next version.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if the entry doesn't exist.
+    pub fn update(&mut self, key: &K, value: &V) -> Result<&mut Self> {
+        let key = utils::serialise(key);
+        let version = self.next_version(&key)?;
+        self.actions.add_action(
+            key,
+            SeqEntryAction::Update(SeqValue {
+                data: utils::serialise(value),
+                version,
+            }),
+        );
+        Ok(self)
+    }
+
+    /// Adds an action deleting an existing entry.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if the entry doesn't exist.
+    pub fn delete(&mut self, key: &K) -> Result<&mut Self> {
+        let key = utils::serialise(key);
+        let version = self.next_version(&key)?;
+        self.actions.add_action(key, SeqEntryAction::Del(version));
+        Ok(self)
+    }
+
+    /// Returns the actions added so far.
+    pub fn actions(&self) -> &SeqEntryActions {
+        &self.actions
+    }
+
+    /// Consumes the view, returning the actions added.
+    pub fn into_actions(self) -> SeqEntryActions {
+        self.actions
+    }
+
+    fn next_version(&self, key: &[u8]) -> Result<u64> {
+        self.data
+            .get(key)
+            .map(|value| value.version + 1)
+            .ok_or(Error::NoSuchEntry)
+    }
+}
+
+/// Typed view of the entries of an unsequenced MutableData, with keys of type `K` and values of
+/// type `V`.
+///
+/// Mutations are accumulated into `UnseqEntryActions`.
+pub struct TypedUnseqData<'a, K, V> {
+    data: &'a UnseqData,
+    actions: UnseqEntryActions,
+    _types: PhantomData<fn(K, V)>,
+}
+
+impl<'a, K, V> TypedUnseqData<'a, K, V>
+where
+    K: Serialize + DeserializeOwned + Ord,
+    V: Serialize + DeserializeOwned,
+{
+    /// Creates a typed view of `data`.
+    pub fn new(data: &'a UnseqData) -> Self {
+        Self {
+            data,
+            actions: UnseqEntryActions::new(),
+            _types: PhantomData,
+        }
+    }
+
+    /// Returns the value of the entry at `key`, if it exists.
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        self.data
+            .get(&utils::serialise(key))
+            .map(|value| utils::deserialise(value))
+            .transpose()
+    }
+
+    /// Returns all the entries.
+    pub fn entries(&self) -> Result<BTreeMap<K, V>> {
+        self.data
+            .entries()
+            .iter()
+            .map(|(key, value)| Ok((utils::deserialise(key)?, utils::deserialise(value)?)))
+            .collect()
+    }
+
+    /// Adds an action inserting a new entry.
+    ///
+    /// Returns `Err(Error::InvalidEntryActions(_))` holding `EntryError::EntryExists` if the entry
+    /// already exists.
+    pub fn insert(&mut self, key: &K, value: &V) -> Result<&mut Self> {
+        let key = utils::serialise(key);
+        if self.data.get(&key).is_some() {
+            return Err(entry_exists(key, 0));
+        }
+        self.actions
+            .add_action(key, UnseqEntryAction::Ins(utils::serialise(value)));
+        Ok(self)
+    }
+
+    /// Adds an action updating an existing entry.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if the entry doesn't exist.
+    pub fn update(&mut self, key: &K, value: &V) -> Result<&mut Self> {
+        let key = self.existing_key(key)?;
+        self.actions
+            .add_action(key, UnseqEntryAction::Update(utils::serialise(value)));
+        Ok(self)
+    }
+
+    /// Adds an action deleting an existing entry.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if the entry doesn't exist.
+    pub fn delete(&mut self, key: &K) -> Result<&mut Self> {
+        let key = self.existing_key(key)?;
+        self.actions.add_action(key, UnseqEntryAction::Del);
+        Ok(self)
+    }
+
+    /// Returns the actions added so far.
+    pub fn actions(&self) -> &UnseqEntryActions {
+        &self.actions
+    }
+
+    /// Consumes the view, returning the actions added.
+    pub fn into_actions(self) -> UnseqEntryActions {
+        self.actions
+    }
+
+    fn existing_key(&self, key: &K) -> Result<Vec<u8>> {
+        let key = utils::serialise(key);
+        if self.data.get(&key).is_none() {
+            return Err(Error::NoSuchEntry);
+        }
+        Ok(key)
+    }
+}
+
+fn entry_exists(key: Vec<u8>, version: u64) -> Error {
+    Error::InvalidEntryActions(iter::once((key, EntryError::EntryExists(version as u8))).collect())
+}
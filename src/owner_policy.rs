@@ -0,0 +1,217 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Module providing owner policies, defining who owns a piece of data.
+//!
+//! Data can be owned by a single key, by a set of keys of which a number must sign together, or
+//! by the holders of the shares of a BLS threshold key, who sign with a signature combined from
+//! their signature shares.
+
+use crate::{Error, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Policy defining the owner(s) of a piece of data.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum OwnerPolicy {
+    /// Owned by a single key.
+    Key(PublicKey),
+    /// Owned by a set of keys, of which `threshold` must sign.
+    Keys {
+        /// Owner keys.
+        keys: BTreeSet<PublicKey>,
+        /// Number of owner keys which must sign.
+        threshold: usize,
+    },
+    /// Owned by the holders of the shares of a BLS threshold key, who sign with a signature
+    /// combined from their signature shares.
+    Threshold(threshold_crypto::PublicKey),
+}
+
+impl OwnerPolicy {
+    /// Constructs a policy requiring the signatures of `threshold` of the `keys`.
+    ///
+    /// Returns `Err::InvalidOwners` if `threshold` is zero or greater than the number of keys.
+    pub fn new_keys(keys: BTreeSet<PublicKey>, threshold: usize) -> Result<Self> {
+        let policy = Self::Keys { keys, threshold };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Checks that the policy can be satisfied. A deserialised policy is not checked on
+    /// construction, so this is checked again whenever the policy is used.
+    ///
+    /// Returns `Err::InvalidOwners` if the threshold is zero or greater than the number of keys.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Keys { keys, threshold } if *threshold == 0 || *threshold > keys.len() => {
+                Err(Error::InvalidOwners)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the keys which can sign for the owner(s). For a BLS threshold key, this is the
+    /// key the combined signatures are checked against.
+    pub fn keys(&self) -> BTreeSet<PublicKey> {
+        match self {
+            Self::Key(key) => vec![*key].into_iter().collect(),
+            Self::Keys { keys, .. } => keys.clone(),
+            Self::Threshold(key) => vec![PublicKey::Bls(*key)].into_iter().collect(),
+        }
+    }
+
+    /// Returns the number of keys which must sign for the owner(s).
+    pub fn threshold(&self) -> usize {
+        match self {
+            Self::Keys { threshold, .. } => *threshold,
+            Self::Key(_) | Self::Threshold(_) => 1,
+        }
+    }
+
+    /// Returns `true` if `requester` can act as the owner(s) on its own, and the policy is valid.
+    pub fn is_satisfied_by(&self, requester: &PublicKey) -> bool {
+        if self.validate().is_err() {
+            return false;
+        }
+        match self {
+            Self::Key(key) => key == requester,
+            Self::Keys { keys, threshold } => *threshold == 1 && keys.contains(requester),
+            Self::Threshold(key) => requester.bls() == Some(*key),
+        }
+    }
+
+    /// Verifies that `signatures` of `data` are enough to act as the owner(s). For a BLS
+    /// threshold key, the combined signature is expected for `PublicKey::Bls` of that key.
+    /// Signatures of keys which are not owner keys are ignored.
+    ///
+    /// Returns:
+    /// `Ok(())` if enough owner keys signed,
+    /// `Err::InvalidOwners` if the policy is not valid,
+    /// `Err::InvalidSignature` if the signature of an owner key is not valid,
+    /// `Err::AccessDenied` if not enough owner keys signed.
+    pub fn verify<T: AsRef<[u8]>>(
+        &self,
+        signatures: &BTreeMap<PublicKey, Signature>,
+        data: T,
+    ) -> Result<()> {
+        self.validate()?;
+        let mut signed = 0;
+        for key in self.keys() {
+            if let Some(signature) = signatures.get(&key) {
+                key.verify(signature, data.as_ref())?;
+                signed += 1;
+            }
+        }
+
+        if signed >= self.threshold() {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+}
+
+impl From<PublicKey> for OwnerPolicy {
+    fn from(key: PublicKey) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<threshold_crypto::PublicKey> for OwnerPolicy {
+    fn from(key: threshold_crypto::PublicKey) -> Self {
+        Self::Threshold(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OwnerPolicy;
+    use crate::{ClientFullId, Error, PublicKey, Result};
+    use std::{collections::BTreeMap, iter};
+    use threshold_crypto::SecretKeySet;
+    use unwrap::unwrap;
+
+    #[test]
+    fn keys_policy() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let ids: Vec<_> = (0..3)
+            .map(|_| ClientFullId::new_ed25519(&mut rng))
+            .collect();
+        let keys = ids.iter().map(|id| *id.public_id().public_key()).collect();
+        let stranger = ClientFullId::new_ed25519(&mut rng);
+
+        assert_eq!(
+            OwnerPolicy::new_keys(Default::default(), 0),
+            Err(Error::InvalidOwners)
+        );
+        let policy = OwnerPolicy::new_keys(keys, 2)?;
+        assert!(!policy.is_satisfied_by(ids[0].public_id().public_key()));
+
+        let data = b"data";
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(*ids[0].public_id().public_key(), ids[0].sign(data));
+        let _ = signatures.insert(*stranger.public_id().public_key(), stranger.sign(data));
+        assert_eq!(policy.verify(&signatures, data), Err(Error::AccessDenied));
+
+        let _ = signatures.insert(*ids[1].public_id().public_key(), ids[2].sign(data));
+        assert_eq!(
+            policy.verify(&signatures, data),
+            Err(Error::InvalidSignature)
+        );
+
+        let _ = signatures.insert(*ids[1].public_id().public_key(), ids[1].sign(data));
+        policy.verify(&signatures, data)?;
+
+        // a deserialised policy bypassing the checks of its construction is still checked
+        let key = *ids[0].public_id().public_key();
+        let policy: OwnerPolicy = unwrap!(bincode::deserialize(&unwrap!(bincode::serialize(&(
+            1u32,
+            vec![key],
+            0usize
+        )))));
+        assert_eq!(
+            policy,
+            OwnerPolicy::Keys {
+                keys: iter::once(key).collect(),
+                threshold: 0
+            }
+        );
+        assert_eq!(
+            policy.verify(&BTreeMap::new(), data),
+            Err(Error::InvalidOwners)
+        );
+        assert!(!policy.is_satisfied_by(&key));
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_policy() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let secret_key_set = SecretKeySet::random(1, &mut rng);
+        let public_key_set = secret_key_set.public_keys();
+        let policy = OwnerPolicy::from(public_key_set.public_key());
+        assert!(policy.is_satisfied_by(&PublicKey::Bls(public_key_set.public_key())));
+
+        let data = b"data";
+        let shares: BTreeMap<_, _> = (0..2)
+            .map(|i| (i, secret_key_set.secret_key_share(i).sign(data)))
+            .collect();
+        let signature = public_key_set
+            .combine_signatures(&shares)
+            .map_err(|_| Error::InvalidSignature)?;
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(
+            PublicKey::Bls(public_key_set.public_key()),
+            signature.into(),
+        );
+        policy.verify(&signatures, data)
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2020 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Module providing validity bounds for permission grants.
+//!
+//! The bounds are logical counters which every mutation the grant allows advances, so a grant
+//! can't be used indefinitely: for MutableData they are numbers of mutations of the data, of its
+//! fields and entries alike (`mutations()`), for Sequence they are entries indices.
+//!
+//! A grant for a user which is not in effect is ignored in favour of the permissions for
+//! `Anyone`, and `Error::PermissionExpired` is returned if those don't allow the action either.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Bounds within which a permission grant is in effect. Unbounded by default.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct PermissionValidity {
+    /// First number of mutations or index at which the grant is in effect, if any.
+    pub not_before: Option<u64>,
+    /// Last number of mutations or index at which the grant is in effect, if any.
+    pub not_after: Option<u64>,
+}
+
+impl PermissionValidity {
+    /// Constructs new validity bounds.
+    pub fn new(not_before: impl Into<Option<u64>>, not_after: impl Into<Option<u64>>) -> Self {
+        Self {
+            not_before: not_before.into(),
+            not_after: not_after.into(),
+        }
+    }
+
+    /// Checks whether the grant is in effect at the number of mutations or index `at`.
+    ///
+    /// Returns:
+    /// `Ok(())` if the grant is in effect,
+    /// `Err::AccessDenied` if the grant is not in effect yet,
+    /// `Err::PermissionExpired` if the grant is not in effect anymore.
+    pub fn check(&self, at: u64) -> Result<()> {
+        if matches!(self.not_before, Some(not_before) if at < not_before) {
+            Err(Error::AccessDenied)
+        } else if matches!(self.not_after, Some(not_after) if at > not_after) {
+            Err(Error::PermissionExpired)
+        } else {
+            Ok(())
+        }
+    }
+}
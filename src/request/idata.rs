@@ -19,8 +19,37 @@ pub enum IDataRequest {
     Put(IData),
     /// Get ImmutableData.
     Get(IDataAddress),
+    /// Get a range of bytes of the ImmutableData value. The range is truncated to the end of the
+    /// value.
+    GetRange {
+        /// ImmutableData address.
+        address: IDataAddress,
+        /// Offset of the first byte to get.
+        offset: u64,
+        /// Number of bytes to get.
+        len: u64,
+    },
     /// Delete unpublished ImmutableData.
     DeleteUnpub(IDataAddress),
+    /// Get unpublished ImmutableData stored encrypted at rest, given the key to decrypt it.
+    GetUnpub {
+        /// ImmutableData address.
+        address: IDataAddress,
+        /// Key of the data, the SHA3-256 hash of its value.
+        key: XorName,
+    },
+    /// Get a range of bytes of the value of unpublished ImmutableData stored encrypted at rest,
+    /// given the key to decrypt it. The range is truncated to the end of the value.
+    GetUnpubRange {
+        /// ImmutableData address.
+        address: IDataAddress,
+        /// Key of the data, the SHA3-256 hash of its value.
+        key: XorName,
+        /// Offset of the first byte to get.
+        offset: u64,
+        /// Number of bytes to get.
+        len: u64,
+    },
 }
 
 impl IDataRequest {
@@ -28,8 +57,18 @@ impl IDataRequest {
     pub fn get_type(&self) -> Type {
         use IDataRequest::*;
         match *self {
-            Get(IDataAddress::Pub(_)) => Type::PublicGet,
-            Get(IDataAddress::Unpub(_)) => Type::PrivateGet,
+            Get(IDataAddress::Pub(_))
+            | GetRange {
+                address: IDataAddress::Pub(_),
+                ..
+            } => Type::PublicGet,
+            Get(IDataAddress::Unpub(_))
+            | GetRange {
+                address: IDataAddress::Unpub(_),
+                ..
+            }
+            | GetUnpub { .. }
+            | GetUnpubRange { .. } => Type::PrivateGet,
             Put(_) | DeleteUnpub(_) => Type::Mutation,
         }
     }
@@ -39,7 +78,8 @@ impl IDataRequest {
     pub fn error_response(&self, error: Error) -> Response {
         use IDataRequest::*;
         match *self {
-            Get(_) => Response::GetIData(Err(error)),
+            Get(_) | GetUnpub { .. } => Response::GetIData(Err(error)),
+            GetRange { .. } | GetUnpubRange { .. } => Response::GetIDataRange(Err(error)),
             Put(_) | DeleteUnpub(_) => Response::Mutation(Err(error)),
         }
     }
@@ -48,8 +88,18 @@ impl IDataRequest {
     pub fn authorisation_kind(&self) -> AuthorisationKind {
         use IDataRequest::*;
         match *self {
-            Get(IDataAddress::Pub(_)) => AuthorisationKind::GetPub,
-            Get(IDataAddress::Unpub(_)) => AuthorisationKind::GetPriv,
+            Get(IDataAddress::Pub(_))
+            | GetRange {
+                address: IDataAddress::Pub(_),
+                ..
+            } => AuthorisationKind::GetPub,
+            Get(IDataAddress::Unpub(_))
+            | GetRange {
+                address: IDataAddress::Unpub(_),
+                ..
+            }
+            | GetUnpub { .. }
+            | GetUnpubRange { .. } => AuthorisationKind::GetPriv,
             Put(_) | DeleteUnpub(_) => AuthorisationKind::Mutation,
         }
     }
@@ -58,7 +108,11 @@ impl IDataRequest {
     pub fn dest_address(&self) -> Option<Cow<XorName>> {
         use IDataRequest::*;
         match self {
-            Get(ref address) | DeleteUnpub(ref address) => Some(Cow::Borrowed(address.name())),
+            Get(ref address)
+            | GetRange { ref address, .. }
+            | DeleteUnpub(ref address)
+            | GetUnpub { ref address, .. }
+            | GetUnpubRange { ref address, .. } => Some(Cow::Borrowed(address.name())),
             Put(ref data) => Some(Cow::Borrowed(data.name())),
         }
     }
@@ -73,7 +127,10 @@ impl fmt::Debug for IDataRequest {
             match *self {
                 Put(_) => "PutIData",
                 Get(_) => "GetIData",
+                GetRange { .. } => "GetIDataRange",
                 DeleteUnpub(_) => "DeleteUnpubIData",
+                GetUnpub { .. } => "GetUnpubIData",
+                GetUnpubRange { .. } => "GetUnpubIDataRange",
             }
         )
     }
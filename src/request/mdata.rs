@@ -9,10 +9,12 @@
 
 use super::{AuthorisationKind, Type};
 use crate::{
-    Error, MData, MDataAddress, MDataEntryActions, MDataPermissionSet, PublicKey, Response, XorName,
+    ConvergentMutableData, Error, MData, MDataAddress, MDataEntryActions, MDataKeyRange,
+    MDataKeyScope, MDataMutationOperation, MDataPermissionSet, MDataUser, OwnerPolicy, PublicKey,
+    Response, XorName,
 };
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, collections::BTreeSet, fmt};
 
 /// MutableData request that is sent to vaults.
 #[allow(clippy::large_enum_variant)]
@@ -29,6 +31,13 @@ pub enum MDataRequest {
         /// Key to get.
         key: Vec<u8>,
     },
+    /// Get MutableData values of several keys.
+    GetValues {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Keys to get.
+        keys: BTreeSet<Vec<u8>>,
+    },
     /// Delete MutableData.
     Delete(MDataAddress),
     /// Get MutableData shell.
@@ -41,12 +50,26 @@ pub enum MDataRequest {
     ListKeys(MDataAddress),
     /// List MutableData values.
     ListValues(MDataAddress),
+    /// List a page of the MutableData entries within a range of keys.
+    ListRange {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Range of keys to list.
+        range: MDataKeyRange,
+    },
+    /// Get the changes of MutableData entries recorded in its change log.
+    GetHistory {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Version of the change log to get the changes made after.
+        since_version: u64,
+    },
     /// Set MutableData user permissions.
     SetUserPermissions {
         /// MutableData address.
         address: MDataAddress,
         /// User to set permissions for.
-        user: PublicKey,
+        user: MDataUser,
         /// New permissions.
         permissions: MDataPermissionSet,
         /// Version to set.
@@ -57,7 +80,7 @@ pub enum MDataRequest {
         /// MutableData address.
         address: MDataAddress,
         /// User to delete permissions for.
-        user: PublicKey,
+        user: MDataUser,
         /// Version to delete.
         version: u64,
     },
@@ -65,6 +88,37 @@ pub enum MDataRequest {
     ListPermissions(MDataAddress),
     /// Get MutableData permissions for a user.
     ListUserPermissions {
+        /// MutableData address.
+        address: MDataAddress,
+        /// User to get permissions for.
+        user: MDataUser,
+    },
+    /// Set MutableData user permissions scoped to some of the entries.
+    SetScopedPermissions {
+        /// MutableData address.
+        address: MDataAddress,
+        /// User to set permissions for.
+        user: PublicKey,
+        /// Scope of the entries the permissions apply to.
+        scope: MDataKeyScope,
+        /// New permissions.
+        permissions: MDataPermissionSet,
+        /// Version to set.
+        version: u64,
+    },
+    /// Delete MutableData user permissions scoped to some of the entries.
+    DelScopedPermissions {
+        /// MutableData address.
+        address: MDataAddress,
+        /// User to delete permissions for.
+        user: PublicKey,
+        /// Scope of the entries the permissions apply to.
+        scope: MDataKeyScope,
+        /// Version to delete.
+        version: u64,
+    },
+    /// List MutableData permissions scoped to some of the entries for a user.
+    ListScopedPermissions {
         /// MutableData address.
         address: MDataAddress,
         /// User to get permissions for.
@@ -77,6 +131,31 @@ pub enum MDataRequest {
         /// Mutation actions to perform.
         actions: MDataEntryActions,
     },
+    /// Start transferring MutableData to a new owner, who then has to accept it with
+    /// `AcceptOwner`. Only the current owner(s) can perform this action.
+    ChangeOwner {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Owner(s) to transfer the data to.
+        new_owner: OwnerPolicy,
+        /// Version to set.
+        version: u64,
+    },
+    /// Accept the pending transfer of MutableData. Only the pending owner(s) can perform this
+    /// action.
+    AcceptOwner {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Version to set.
+        version: u64,
+    },
+    /// Put convergent MutableData.
+    PutConvergent(ConvergentMutableData),
+    /// Get convergent MutableData.
+    GetConvergent(MDataAddress),
+    /// Apply an operation to convergent MutableData. The operation MUST have been issued by the
+    /// requester.
+    ApplyConvergentOp(MDataMutationOperation),
 }
 
 impl MDataRequest {
@@ -85,21 +164,37 @@ impl MDataRequest {
         use MDataRequest::*;
 
         match *self {
-            // MData requests (always unpub)
-            Get(_)
-            | GetValue { .. }
-            | GetShell(_)
-            | GetVersion(_)
-            | ListEntries(_)
-            | ListKeys(_)
-            | ListValues(_)
-            | ListPermissions(_)
-            | ListUserPermissions { .. } => Type::PrivateGet,
+            Get(address)
+            | GetValue { address, .. }
+            | GetValues { address, .. }
+            | GetShell(address)
+            | GetVersion(address)
+            | ListEntries(address)
+            | ListKeys(address)
+            | ListValues(address)
+            | ListRange { address, .. }
+            | GetHistory { address, .. }
+            | ListPermissions(address)
+            | ListUserPermissions { address, .. }
+            | ListScopedPermissions { address, .. }
+            | GetConvergent(address) => {
+                if address.is_pub() {
+                    Type::PublicGet
+                } else {
+                    Type::PrivateGet
+                }
+            }
             Put(_)
             | Delete(_)
             | SetUserPermissions { .. }
             | DelUserPermissions { .. }
-            | MutateEntries { .. } => Type::Mutation,
+            | SetScopedPermissions { .. }
+            | DelScopedPermissions { .. }
+            | MutateEntries { .. }
+            | ChangeOwner { .. }
+            | AcceptOwner { .. }
+            | PutConvergent(_)
+            | ApplyConvergentOp(_) => Type::Mutation,
         }
     }
 
@@ -111,18 +206,29 @@ impl MDataRequest {
         match *self {
             Get(_) => Response::GetMData(Err(error)),
             GetValue { .. } => Response::GetMDataValue(Err(error)),
+            GetValues { .. } => Response::GetMDataValues(Err(error)),
             GetShell(_) => Response::GetMDataShell(Err(error)),
             GetVersion(_) => Response::GetMDataVersion(Err(error)),
             ListEntries(_) => Response::ListMDataEntries(Err(error)),
             ListKeys(_) => Response::ListMDataKeys(Err(error)),
             ListValues(_) => Response::ListMDataValues(Err(error)),
+            ListRange { .. } => Response::ListMDataRange(Err(error)),
+            GetHistory { .. } => Response::GetMDataHistory(Err(error)),
             ListPermissions(_) => Response::ListMDataPermissions(Err(error)),
             ListUserPermissions { .. } => Response::ListMDataUserPermissions(Err(error)),
+            ListScopedPermissions { .. } => Response::ListMDataScopedPermissions(Err(error)),
+            GetConvergent(_) => Response::GetConvergentMData(Err(error)),
             Put(_)
             | Delete(_)
             | SetUserPermissions { .. }
             | DelUserPermissions { .. }
-            | MutateEntries { .. } => Response::Mutation(Err(error)),
+            | SetScopedPermissions { .. }
+            | DelScopedPermissions { .. }
+            | MutateEntries { .. }
+            | ChangeOwner { .. }
+            | AcceptOwner { .. }
+            | PutConvergent(_)
+            | ApplyConvergentOp(_) => Response::Mutation(Err(error)),
         }
     }
 
@@ -134,16 +240,33 @@ impl MDataRequest {
             | Delete(_)
             | SetUserPermissions { .. }
             | DelUserPermissions { .. }
-            | MutateEntries { .. } => AuthorisationKind::Mutation,
-            Get(_)
-            | GetValue { .. }
-            | GetShell(_)
-            | GetVersion(_)
-            | ListEntries(_)
-            | ListKeys(_)
-            | ListValues(_)
-            | ListPermissions(_)
-            | ListUserPermissions { .. } => AuthorisationKind::GetPriv,
+            | SetScopedPermissions { .. }
+            | DelScopedPermissions { .. }
+            | MutateEntries { .. }
+            | ChangeOwner { .. }
+            | PutConvergent(_)
+            | ApplyConvergentOp(_) => AuthorisationKind::Mutation,
+            AcceptOwner { .. } => AuthorisationKind::PendingOwnerMutation,
+            Get(address)
+            | GetValue { address, .. }
+            | GetValues { address, .. }
+            | GetShell(address)
+            | GetVersion(address)
+            | ListEntries(address)
+            | ListKeys(address)
+            | ListValues(address)
+            | ListRange { address, .. }
+            | GetHistory { address, .. }
+            | ListPermissions(address)
+            | ListUserPermissions { address, .. }
+            | ListScopedPermissions { address, .. }
+            | GetConvergent(address) => {
+                if address.is_pub() {
+                    AuthorisationKind::GetPub
+                } else {
+                    AuthorisationKind::GetPriv
+                }
+            }
         }
     }
 
@@ -152,18 +275,29 @@ impl MDataRequest {
         use MDataRequest::*;
         match self {
             Put(ref data) => Some(Cow::Borrowed(data.name())),
+            PutConvergent(ref data) => Some(Cow::Borrowed(data.name())),
+            ApplyConvergentOp(ref op) => Some(Cow::Borrowed(op.address.name())),
             Get(ref address)
             | GetValue { ref address, .. }
+            | GetValues { ref address, .. }
             | Delete(ref address)
             | GetShell(ref address)
             | GetVersion(ref address)
             | ListEntries(ref address)
             | ListKeys(ref address)
             | ListValues(ref address)
+            | ListRange { ref address, .. }
+            | GetHistory { ref address, .. }
             | SetUserPermissions { ref address, .. }
             | DelUserPermissions { ref address, .. }
             | ListPermissions(ref address)
             | ListUserPermissions { ref address, .. }
+            | SetScopedPermissions { ref address, .. }
+            | DelScopedPermissions { ref address, .. }
+            | ListScopedPermissions { ref address, .. }
+            | GetConvergent(ref address)
+            | ChangeOwner { ref address, .. }
+            | AcceptOwner { ref address, .. }
             | MutateEntries { ref address, .. } => Some(Cow::Borrowed(address.name())),
         }
     }
@@ -180,17 +314,28 @@ impl fmt::Debug for MDataRequest {
                 Put(_) => "PutMData",
                 Get(_) => "GetMData",
                 GetValue { .. } => "GetMDataValue",
+                GetValues { .. } => "GetMDataValues",
                 Delete(_) => "DeleteMData",
                 GetShell(_) => "GetMDataShell",
                 GetVersion(_) => "GetMDataVersion",
                 ListEntries(_) => "ListMDataEntries",
                 ListKeys(_) => "ListMDataKeys",
                 ListValues(_) => "ListMDataValues",
+                ListRange { .. } => "ListMDataRange",
+                GetHistory { .. } => "GetMDataHistory",
                 SetUserPermissions { .. } => "SetMDataUserPermissions",
                 DelUserPermissions { .. } => "DelMDataUserPermissions",
                 ListPermissions(_) => "ListMDataPermissions",
                 ListUserPermissions { .. } => "ListMDataUserPermissions",
+                SetScopedPermissions { .. } => "SetMDataScopedPermissions",
+                DelScopedPermissions { .. } => "DelMDataScopedPermissions",
+                ListScopedPermissions { .. } => "ListMDataScopedPermissions",
                 MutateEntries { .. } => "MutateMDataEntries",
+                ChangeOwner { .. } => "ChangeMDataOwner",
+                AcceptOwner { .. } => "AcceptMDataOwner",
+                PutConvergent(_) => "PutConvergentMData",
+                GetConvergent(_) => "GetConvergentMData",
+                ApplyConvergentOp(_) => "ApplyConvergentMDataOp",
             }
         )
     }
@@ -53,6 +53,9 @@ pub enum AuthorisationKind {
     TransferCoins,
     /// Request to mutate and transfer coins
     MutAndTransferCoins,
+    /// Mutation request to be authorised against the pending owner(s) of the data rather than
+    /// its current owner(s).
+    PendingOwnerMutation,
 }
 
 /// RPC Request that is sent to vaults.
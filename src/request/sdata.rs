@@ -9,8 +9,9 @@
 
 use super::{AuthorisationKind, Type};
 use crate::{
-    Error, Response, SData, SDataAddress, SDataEntry, SDataIndex, SDataMutationOperation,
-    SDataOwner, SDataPrivPermissions, SDataPubPermissions, SDataUser, XorName,
+    Error, Response, SData, SDataAddress, SDataEntry, SDataIndex, SDataOwner, SDataPrivPermissions,
+    SDataPubPermissions, SDataSignedCheckpoint, SDataSignedOperation, SDataSummary, SDataUser,
+    XorName,
 };
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, fmt};
@@ -44,8 +45,31 @@ pub enum SDataRequest {
         /// range: (Index::FromStart(0), Index::FromStart(5))
         range: (SDataIndex, SDataIndex),
     },
+    /// Get a page of entries from a range of an Sequence object on the network.
+    GetRangePage {
+        /// Sequence address.
+        address: SDataAddress,
+        /// Range of entries to fetch, as for `GetRange`. To fetch the following page, the start
+        /// of the range is set to the `next` index of the previous page.
+        range: (SDataIndex, SDataIndex),
+        /// Maximum number of entries in the page.
+        max_entries: Option<u64>,
+        /// Maximum number of bytes of entries in the page. The page always holds at least one
+        /// entry if the range isn't empty.
+        max_bytes: Option<u64>,
+    },
     /// Get last entry from the Sequence.
     GetLastEntry(SDataAddress),
+    /// Get the CRDT operations of a Sequence which are missing from a replica.
+    GetDelta {
+        /// Sequence address.
+        address: SDataAddress,
+        /// Summary of the operations the replica already holds.
+        summary: SDataSummary,
+    },
+    /// Get the checkpoint the permissions and owners history of the Sequence has been folded
+    /// into.
+    GetCheckpoint(SDataAddress),
     /// List all current users permissions.
     GetPermissions(SDataAddress),
     /// Get current permissions for a specified user(s).
@@ -57,14 +81,27 @@ pub enum SDataRequest {
     },
     /// Get current owner.
     GetOwner(SDataAddress),
+    /// List the concurrent ownership changes, keyed by the owners index they were made from.
+    GetOwnerForks(SDataAddress),
     /// Set new permissions for public Sequence.
-    MutatePubPermissions(SDataMutationOperation<SDataPubPermissions>),
+    MutatePubPermissions(SDataSignedOperation<SDataPubPermissions>),
     /// Set new permissions for private Sequence.
-    MutatePrivPermissions(SDataMutationOperation<SDataPrivPermissions>),
+    MutatePrivPermissions(SDataSignedOperation<SDataPrivPermissions>),
     /// Add a new `owners` entry. Only the current owner(s) can perform this action.
-    MutateOwner(SDataMutationOperation<SDataOwner>),
+    MutateOwner(SDataSignedOperation<SDataOwner>),
     /// Mutate the Sequence (insert/remove entry).
-    Mutate(SDataMutationOperation<SDataEntry>),
+    Mutate(SDataSignedOperation<SDataEntry>),
+    /// Insert an entry at a given position in the Sequence.
+    ///
+    /// This operation MUST return an error if the operation is not an insertion.
+    InsertEntry(SDataSignedOperation<SDataEntry>),
+    /// Delete an entry from the Sequence.
+    ///
+    /// This operation MUST return an error if the operation is not a deletion.
+    DeleteEntry(SDataSignedOperation<SDataEntry>),
+    /// Fold the permissions and owners history of the Sequence into a checkpoint signed by the
+    /// owner in effect at its entries index.
+    Compact(SDataSignedCheckpoint),
 }
 
 impl SDataRequest {
@@ -75,10 +112,14 @@ impl SDataRequest {
         match *self {
             Get(address)
             | GetRange { address, .. }
+            | GetRangePage { address, .. }
             | GetLastEntry(address)
+            | GetDelta { address, .. }
+            | GetCheckpoint(address)
             | GetPermissions(address)
             | GetUserPermissions { address, .. }
-            | GetOwner(address) => {
+            | GetOwner(address)
+            | GetOwnerForks(address) => {
                 if address.is_pub() {
                     Type::PublicGet
                 } else {
@@ -90,7 +131,10 @@ impl SDataRequest {
             | MutatePubPermissions(_)
             | MutatePrivPermissions(_)
             | MutateOwner(_)
-            | Mutate(_) => Type::Mutation,
+            | Mutate(_)
+            | InsertEntry(_)
+            | DeleteEntry(_)
+            | Compact(_) => Type::Mutation,
         }
     }
 
@@ -102,16 +146,23 @@ impl SDataRequest {
         match *self {
             Get(_) => Response::GetSData(Err(error)),
             GetRange { .. } => Response::GetSDataRange(Err(error)),
+            GetRangePage { .. } => Response::GetSDataRangePage(Err(error)),
             GetLastEntry(_) => Response::GetSDataLastEntry(Err(error)),
+            GetDelta { .. } => Response::GetSDataDelta(Err(error)),
+            GetCheckpoint(_) => Response::GetSDataCheckpoint(Err(error)),
             GetPermissions(_) => Response::GetSDataPermissions(Err(error)),
             GetUserPermissions { .. } => Response::GetSDataUserPermissions(Err(error)),
             GetOwner(_) => Response::GetSDataOwner(Err(error)),
+            GetOwnerForks(_) => Response::GetSDataOwnerForks(Err(error)),
             Store(_)
             | Delete(_)
             | MutatePubPermissions(_)
             | MutatePrivPermissions(_)
             | MutateOwner(_)
-            | Mutate(_) => Response::Mutation(Err(error)),
+            | Mutate(_)
+            | InsertEntry(_)
+            | DeleteEntry(_)
+            | Compact(_) => Response::Mutation(Err(error)),
         }
     }
 
@@ -124,13 +175,20 @@ impl SDataRequest {
             | MutatePubPermissions(_)
             | MutatePrivPermissions(_)
             | MutateOwner(_)
-            | Mutate(_) => AuthorisationKind::Mutation,
+            | Mutate(_)
+            | InsertEntry(_)
+            | DeleteEntry(_)
+            | Compact(_) => AuthorisationKind::Mutation,
             Get(address)
             | GetRange { address, .. }
+            | GetRangePage { address, .. }
             | GetLastEntry(address)
+            | GetDelta { address, .. }
+            | GetCheckpoint(address)
             | GetPermissions(address)
             | GetUserPermissions { address, .. }
-            | GetOwner(address) => {
+            | GetOwner(address)
+            | GetOwnerForks(address) => {
                 if address.is_pub() {
                     AuthorisationKind::GetPub
                 } else {
@@ -148,14 +206,25 @@ impl SDataRequest {
             Get(ref address)
             | Delete(ref address)
             | GetRange { ref address, .. }
-            | GetLastEntry(ref address) => Some(Cow::Borrowed(address.name())),
+            | GetRangePage { ref address, .. }
+            | GetLastEntry(ref address)
+            | GetDelta { ref address, .. }
+            | GetCheckpoint(ref address) => Some(Cow::Borrowed(address.name())),
             GetPermissions(ref address)
             | GetUserPermissions { ref address, .. }
-            | GetOwner(ref address) => Some(Cow::Borrowed(address.name())),
-            MutatePubPermissions(ref op) => Some(Cow::Borrowed(op.address.name())),
-            MutatePrivPermissions(ref op) => Some(Cow::Borrowed(op.address.name())),
-            MutateOwner(ref op) => Some(Cow::Borrowed(op.address.name())),
-            Mutate(ref op) => Some(Cow::Borrowed(op.address.name())),
+            | GetOwner(ref address)
+            | GetOwnerForks(ref address) => Some(Cow::Borrowed(address.name())),
+            MutatePubPermissions(ref signed_op) => Some(Cow::Borrowed(signed_op.op.address.name())),
+            MutatePrivPermissions(ref signed_op) => {
+                Some(Cow::Borrowed(signed_op.op.address.name()))
+            }
+            MutateOwner(ref signed_op) => Some(Cow::Borrowed(signed_op.op.address.name())),
+            Mutate(ref signed_op) | InsertEntry(ref signed_op) | DeleteEntry(ref signed_op) => {
+                Some(Cow::Borrowed(signed_op.op.address.name()))
+            }
+            Compact(ref signed_checkpoint) => {
+                Some(Cow::Borrowed(signed_checkpoint.checkpoint.address.name()))
+            }
         }
     }
 }
@@ -172,14 +241,21 @@ impl fmt::Debug for SDataRequest {
                 Get(_) => "GetSData",
                 Delete(_) => "DeleteSData",
                 GetRange { .. } => "GetSDataRange",
+                GetRangePage { .. } => "GetSDataRangePage",
                 GetLastEntry(_) => "GetSDataLastEntry",
+                GetDelta { .. } => "GetSDataDelta",
+                GetCheckpoint(_) => "GetSDataCheckpoint",
                 GetPermissions { .. } => "GetSDataPermissions",
                 GetUserPermissions { .. } => "GetSDataUserPermissions",
                 GetOwner { .. } => "GetSDataOwner",
+                GetOwnerForks(_) => "GetSDataOwnerForks",
                 MutatePubPermissions(_) => "MutateSDataPubPermissions",
                 MutatePrivPermissions(_) => "MutateSDataPrivPermissions",
                 MutateOwner(_) => "MutateSDataOwner",
                 Mutate(_) => "MutateSData",
+                InsertEntry(_) => "InsertSDataEntry",
+                DeleteEntry(_) => "DeleteSDataEntry",
+                Compact(_) => "CompactSData",
             }
         )
     }
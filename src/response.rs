@@ -8,15 +8,17 @@
 // Software.
 
 use crate::{
-    errors::ErrorDebug, AppPermissions, Coins, Error, IData, MData, MDataEntries,
-    MDataPermissionSet, MDataValue, MDataValues, PublicKey, Result, SData, SDataEntries,
-    SDataEntry, SDataOwner, SDataPermissions, SDataUserPermissions, Signature, Transaction,
+    errors::ErrorDebug, AppPermissions, Coins, ConvergentMutableData, Error, IData, IDataAddress,
+    MData, MDataChange, MDataEntries, MDataKeyScope, MDataPage, MDataPermissionSet, MDataUser,
+    MDataValue, MDataValueResults, MDataValues, PublicKey, Result, SData, SDataDelta, SDataEntries,
+    SDataEntry, SDataOwner, SDataPage, SDataPermissions, SDataSignedCheckpoint,
+    SDataUserPermissions, Signature, Transaction,
 };
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
-    fmt,
+    fmt, result,
 };
 
 /// RPC responses from vaults.
@@ -28,6 +30,8 @@ pub enum Response {
     //
     /// Get ImmutableData.
     GetIData(Result<IData>),
+    /// Get a range of bytes of an ImmutableData value.
+    GetIDataRange(Result<Vec<u8>>),
     //
     // ===== Mutable Data =====
     //
@@ -43,12 +47,22 @@ pub enum Response {
     ListMDataKeys(Result<BTreeSet<Vec<u8>>>),
     /// List all MutableData values.
     ListMDataValues(Result<MDataValues>),
+    /// List a page of the MutableData entries within a range of keys.
+    ListMDataRange(Result<MDataPage>),
+    /// Get the changes of MutableData entries recorded in its change log.
+    GetMDataHistory(Result<Vec<MDataChange>>),
     /// Get MutableData permissions for a user.
     ListMDataUserPermissions(Result<MDataPermissionSet>),
     /// List all MutableData permissions.
-    ListMDataPermissions(Result<BTreeMap<PublicKey, MDataPermissionSet>>),
+    ListMDataPermissions(Result<BTreeMap<MDataUser, MDataPermissionSet>>),
+    /// Get MutableData permissions scoped to some of the entries for a user.
+    ListMDataScopedPermissions(Result<BTreeMap<MDataKeyScope, MDataPermissionSet>>),
     /// Get MutableData value.
     GetMDataValue(Result<MDataValue>),
+    /// Get MutableData values of several keys.
+    GetMDataValues(Result<MDataValueResults>),
+    /// Get convergent MutableData.
+    GetConvergentMData(Result<ConvergentMutableData>),
     //
     // ===== Sequence Data =====
     //
@@ -56,10 +70,18 @@ pub enum Response {
     GetSData(Result<SData>),
     /// Get Sequence owners.
     GetSDataOwner(Result<SDataOwner>),
+    /// Get Sequence concurrent ownership changes.
+    GetSDataOwnerForks(Result<BTreeMap<u64, Vec<SDataOwner>>>),
     /// Get Sequence entries from a range.
     GetSDataRange(Result<SDataEntries>),
+    /// Get Sequence range page.
+    GetSDataRangePage(Result<SDataPage>),
     /// Get Sequence last entry.
     GetSDataLastEntry(Result<(u64, SDataEntry)>),
+    /// Get the Sequence CRDT operations missing from a replica.
+    GetSDataDelta(Result<SDataDelta>),
+    /// Get the checkpoint of a Sequence history.
+    GetSDataCheckpoint(Result<SDataSignedCheckpoint>),
     /// List all Sequence permissions at the provided index.
     GetSDataPermissions(Result<SDataPermissions>),
     /// Get Sequence permissions for a user.
@@ -98,6 +120,20 @@ pub enum TryFromError {
     Response(Error),
 }
 
+impl Response {
+    /// Extracts the ImmutableData from a `GetIData` response, after verifying that it is the data
+    /// stored at `address`.
+    ///
+    /// Returns `Err(TryFromError::Response(Error::ContentMismatch))` if the data doesn't match
+    /// `address`.
+    pub fn verified_idata(self, address: &IDataAddress) -> result::Result<IData, TryFromError> {
+        let data = IData::try_from(self)?;
+        data.verify_against(address)
+            .map_err(TryFromError::Response)?;
+        Ok(data)
+    }
+}
+
 macro_rules! try_from {
     ($ok_type:ty, $($variant:ident),*) => {
         impl TryFrom<Response> for $ok_type {
@@ -116,18 +152,31 @@ macro_rules! try_from {
 }
 
 try_from!(IData, GetIData);
+try_from!(Vec<u8>, GetIDataRange);
 try_from!(MData, GetMData, GetMDataShell);
 try_from!(u64, GetMDataVersion);
 try_from!(MDataEntries, ListMDataEntries);
 try_from!(BTreeSet<Vec<u8>>, ListMDataKeys);
 try_from!(MDataValues, ListMDataValues);
+try_from!(MDataPage, ListMDataRange);
+try_from!(Vec<MDataChange>, GetMDataHistory);
 try_from!(MDataPermissionSet, ListMDataUserPermissions);
-try_from!(BTreeMap<PublicKey, MDataPermissionSet>, ListMDataPermissions);
+try_from!(BTreeMap<MDataUser, MDataPermissionSet>, ListMDataPermissions);
+try_from!(
+    BTreeMap<MDataKeyScope, MDataPermissionSet>,
+    ListMDataScopedPermissions
+);
 try_from!(MDataValue, GetMDataValue);
+try_from!(MDataValueResults, GetMDataValues);
+try_from!(ConvergentMutableData, GetConvergentMData);
 try_from!(SData, GetSData);
 try_from!(SDataOwner, GetSDataOwner);
+try_from!(BTreeMap<u64, Vec<SDataOwner>>, GetSDataOwnerForks);
 try_from!(SDataEntries, GetSDataRange);
+try_from!(SDataPage, GetSDataRangePage);
 try_from!((u64, SDataEntry), GetSDataLastEntry);
+try_from!(SDataDelta, GetSDataDelta);
+try_from!(SDataSignedCheckpoint, GetSDataCheckpoint);
 try_from!(SDataPermissions, GetSDataPermissions);
 try_from!(SDataUserPermissions, GetSDataUserPermissions);
 try_from!(Coins, GetBalance);
@@ -146,6 +195,7 @@ impl fmt::Debug for Response {
         match self {
             // IData
             GetIData(res) => write!(f, "Response::GetIData({:?})", ErrorDebug(res)),
+            GetIDataRange(res) => write!(f, "Response::GetIDataRange({:?})", ErrorDebug(res)),
             // MData
             GetMData(res) => write!(f, "Response::GetMData({:?})", ErrorDebug(res)),
             GetMDataShell(res) => write!(f, "Response::GetMDataShell({:?})", ErrorDebug(res)),
@@ -153,6 +203,8 @@ impl fmt::Debug for Response {
             ListMDataEntries(res) => write!(f, "Response::ListMDataEntries({:?})", ErrorDebug(res)),
             ListMDataKeys(res) => write!(f, "Response::ListMDataKeys({:?})", ErrorDebug(res)),
             ListMDataValues(res) => write!(f, "Response::ListMDataValues({:?})", ErrorDebug(res)),
+            ListMDataRange(res) => write!(f, "Response::ListMDataRange({:?})", ErrorDebug(res)),
+            GetMDataHistory(res) => write!(f, "Response::GetMDataHistory({:?})", ErrorDebug(res)),
             ListMDataPermissions(res) => {
                 write!(f, "Response::ListMDataPermissions({:?})", ErrorDebug(res))
             }
@@ -161,13 +213,29 @@ impl fmt::Debug for Response {
                 "Response::ListMDataUserPermissions({:?})",
                 ErrorDebug(res)
             ),
+            ListMDataScopedPermissions(res) => write!(
+                f,
+                "Response::ListMDataScopedPermissions({:?})",
+                ErrorDebug(res)
+            ),
             GetMDataValue(res) => write!(f, "Response::GetMDataValue({:?})", ErrorDebug(res)),
+            GetMDataValues(res) => write!(f, "Response::GetMDataValues({:?})", ErrorDebug(res)),
+            GetConvergentMData(res) => {
+                write!(f, "Response::GetConvergentMData({:?})", ErrorDebug(res))
+            }
             // SData
             GetSData(res) => write!(f, "Response::GetSData({:?})", ErrorDebug(res)),
             GetSDataRange(res) => write!(f, "Response::GetSDataRange({:?})", ErrorDebug(res)),
+            GetSDataRangePage(res) => {
+                write!(f, "Response::GetSDataRangePage({:?})", ErrorDebug(res))
+            }
             GetSDataLastEntry(res) => {
                 write!(f, "Response::GetSDataLastEntry({:?})", ErrorDebug(res))
             }
+            GetSDataDelta(res) => write!(f, "Response::GetSDataDelta({:?})", ErrorDebug(res)),
+            GetSDataCheckpoint(res) => {
+                write!(f, "Response::GetSDataCheckpoint({:?})", ErrorDebug(res))
+            }
             GetSDataPermissions(res) => {
                 write!(f, "Response::GetSDataPermissions({:?})", ErrorDebug(res))
             }
@@ -177,6 +245,9 @@ impl fmt::Debug for Response {
                 ErrorDebug(res)
             ),
             GetSDataOwner(res) => write!(f, "Response::GetSDataOwner({:?})", ErrorDebug(res)),
+            GetSDataOwnerForks(res) => {
+                write!(f, "Response::GetSDataOwnerForks({:?})", ErrorDebug(res))
+            }
             // Coins
             GetBalance(res) => write!(f, "Response::GetBalance({:?})", ErrorDebug(res)),
             Transaction(res) => write!(f, "Response::Transaction({:?})", ErrorDebug(res)),
@@ -230,13 +301,13 @@ mod tests {
         let mut data = BTreeMap::new();
         let _ = data.insert(vec![1], vec![10]);
         let owners = PublicKey::Bls(threshold_crypto::SecretKey::random().public_key());
-        let m_data = MData::Unseq(UnseqMutableData::new_with_data(
+        let m_data = MData::Unseq(unwrap!(UnseqMutableData::new_with_data(
             *i_data.name(),
             1,
             data,
             BTreeMap::new(),
             owners,
-        ));
+        )));
         assert_eq!(m_data, unwrap!(GetMData(Ok(m_data.clone())).try_into()));
         assert_eq!(
             TryFromError::Response(e.clone()),
@@ -247,4 +318,24 @@ mod tests {
             unwrap_err!(MData::try_from(Mutation(Ok(()))))
         );
     }
+
+    #[test]
+    fn verified_idata() {
+        use Response::*;
+
+        let i_data = IData::Pub(PubImmutableData::new(vec![1, 3, 1, 4]));
+        let other = IData::Pub(PubImmutableData::new(vec![2, 7, 1, 8]));
+        assert_eq!(
+            i_data,
+            unwrap!(GetIData(Ok(i_data.clone())).verified_idata(i_data.address()))
+        );
+        assert_eq!(
+            TryFromError::Response(Error::ContentMismatch),
+            unwrap_err!(GetIData(Ok(other)).verified_idata(i_data.address()))
+        );
+        assert_eq!(
+            TryFromError::WrongType,
+            unwrap_err!(Mutation(Ok(())).verified_idata(i_data.address()))
+        );
+    }
 }
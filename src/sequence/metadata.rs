@@ -7,7 +7,8 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, Error, PublicKey, Result, XorName};
+use crate::{utils, Error, OwnerPolicy, PermissionValidity, PublicKey, Result, XorName};
+use crdts::lseq::ident::Identifier;
 use multibase::Decodable;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Debug, hash::Hash};
@@ -19,6 +20,8 @@ pub enum Action {
     Read,
     /// Append to the data.
     Append,
+    /// Delete entries from the data.
+    Delete,
     /// Manage permissions.
     ManagePermissions,
 }
@@ -29,6 +32,19 @@ pub type Entries = Vec<Entry>;
 /// An entry in a Sequence.
 pub type Entry = Vec<u8>;
 
+/// Identifier of an entry in a Sequence, which orders it among the other entries. Unlike its
+/// index, it doesn't change when entries are inserted before it.
+pub type EntryId = Identifier<PublicKey>;
+
+/// A page of entries from a range of a Sequence.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct Page {
+    /// Entries of the page.
+    pub entries: Entries,
+    /// Index to continue from to get the next page, or `None` if the range has been exhausted.
+    pub next: Option<Index>,
+}
+
 /// Address of a Sequence.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Address {
@@ -171,15 +187,21 @@ impl Indices {
 }
 
 /// An owner could represent an individual user, or a group of users,
-/// depending on the `public_key` type.
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+/// depending on the `policy`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
 pub struct Owner {
-    /// Public key.
-    pub public_key: PublicKey,
+    /// Owner policy.
+    pub policy: OwnerPolicy,
     /// The current index of the data when this ownership change happened
     pub entries_index: u64,
+    /// Identifier of the last entry when this ownership change happened, if any. The owner is in
+    /// effect for the entries placed after it
+    pub entries_anchor: Option<EntryId>,
     /// The current index of the permissions when this ownership change happened
     pub permissions_index: u64,
+    /// The current index of the owners when this ownership change happened, i.e. the index of
+    /// this owner unless a concurrent ownership change was ordered before it
+    pub owners_index: u64,
 }
 
 /// Set of public permissions for a user.
@@ -189,18 +211,30 @@ pub struct PubUserPermissions {
     /// `Some(false)` explicitly denies this permission (even if `Anyone` has required permissions).
     /// Use permissions for `Anyone` if `None`.
     append: Option<bool>,
+    /// `Some(true)` if the user can delete entries.
+    /// `Some(false)` explicitly denies this permission (even if `Anyone` has required permissions).
+    /// Use permissions for `Anyone` if `None`.
+    delete: Option<bool>,
     /// `Some(true)` if the user can manage permissions.
     /// `Some(false)` explicitly denies this permission (even if `Anyone` has required permissions).
     /// Use permissions for `Anyone` if `None`.
     manage_permissions: Option<bool>,
+    /// Entries indices within which the permissions are in effect.
+    validity: PermissionValidity,
 }
 
 impl PubUserPermissions {
     /// Constructs a new public permission set.
-    pub fn new(append: impl Into<Option<bool>>, manage_perms: impl Into<Option<bool>>) -> Self {
+    pub fn new(
+        append: impl Into<Option<bool>>,
+        delete: impl Into<Option<bool>>,
+        manage_perms: impl Into<Option<bool>>,
+    ) -> Self {
         Self {
             append: append.into(),
+            delete: delete.into(),
             manage_permissions: manage_perms.into(),
+            validity: Default::default(),
         }
     }
 
@@ -208,18 +242,38 @@ impl PubUserPermissions {
     pub fn set_perms(
         &mut self,
         append: impl Into<Option<bool>>,
+        delete: impl Into<Option<bool>>,
         manage_perms: impl Into<Option<bool>>,
     ) {
         self.append = append.into();
+        self.delete = delete.into();
         self.manage_permissions = manage_perms.into();
     }
 
+    /// Grants the permissions from the given entries index on.
+    pub fn valid_from(mut self, entries_index: u64) -> Self {
+        self.validity.not_before = Some(entries_index);
+        self
+    }
+
+    /// Grants the permissions up to and including the given entries index.
+    pub fn valid_until(mut self, entries_index: u64) -> Self {
+        self.validity.not_after = Some(entries_index);
+        self
+    }
+
+    /// Returns the entries indices within which the permissions are in effect.
+    pub fn validity(self) -> PermissionValidity {
+        self.validity
+    }
+
     /// Returns `Some(true)` if `action` is allowed and `Some(false)` if it's not permitted.
     /// `None` means that default permissions should be applied.
     pub fn is_allowed(self, action: Action) -> Option<bool> {
         match action {
             Action::Read => Some(true), // It's public data, so it's always allowed to read it.
             Action::Append => self.append,
+            Action::Delete => self.delete,
             Action::ManagePermissions => self.manage_permissions,
         }
     }
@@ -232,32 +286,57 @@ pub struct PrivUserPermissions {
     read: bool,
     /// `true` if the user can append.
     append: bool,
+    /// `true` if the user can delete entries.
+    delete: bool,
     /// `true` if the user can manage permissions.
     manage_permissions: bool,
+    /// Entries indices within which the permissions are in effect.
+    validity: PermissionValidity,
 }
 
 impl PrivUserPermissions {
     /// Constructs a new private permission set.
-    pub fn new(read: bool, append: bool, manage_perms: bool) -> Self {
+    pub fn new(read: bool, append: bool, delete: bool, manage_perms: bool) -> Self {
         Self {
             read,
             append,
+            delete,
             manage_permissions: manage_perms,
+            validity: Default::default(),
         }
     }
 
     /// Sets permissions.
-    pub fn set_perms(&mut self, read: bool, append: bool, manage_perms: bool) {
+    pub fn set_perms(&mut self, read: bool, append: bool, delete: bool, manage_perms: bool) {
         self.read = read;
         self.append = append;
+        self.delete = delete;
         self.manage_permissions = manage_perms;
     }
 
+    /// Grants the permissions from the given entries index on.
+    pub fn valid_from(mut self, entries_index: u64) -> Self {
+        self.validity.not_before = Some(entries_index);
+        self
+    }
+
+    /// Grants the permissions up to and including the given entries index.
+    pub fn valid_until(mut self, entries_index: u64) -> Self {
+        self.validity.not_after = Some(entries_index);
+        self
+    }
+
+    /// Returns the entries indices within which the permissions are in effect.
+    pub fn validity(self) -> PermissionValidity {
+        self.validity
+    }
+
     /// Returns `true` if `action` is allowed.
     pub fn is_allowed(self, action: Action) -> bool {
         match action {
             Action::Read => self.read,
             Action::Append => self.append,
+            Action::Delete => self.delete,
             Action::ManagePermissions => self.manage_permissions,
         }
     }
@@ -279,17 +358,30 @@ pub struct PubPermissions {
     pub permissions: BTreeMap<User, PubUserPermissions>,
     /// The current index of the data when this permission change happened.
     pub entries_index: u64,
+    /// Identifier of the last entry when this permission change happened, if any. The
+    /// permissions are in effect for the entries placed after it.
+    pub entries_anchor: Option<EntryId>,
     /// The current index of the owners when this permission change happened.
     pub owners_index: u64,
 }
 
 impl PubPermissions {
     /// Returns `Some(true)` if `action` is allowed for the provided user and `Some(false)` if it's
-    /// not permitted. `None` means that default permissions should be applied.
-    fn is_action_allowed_by_user(&self, user: &User, action: Action) -> Option<bool> {
-        self.permissions
-            .get(user)
-            .and_then(|perms| perms.is_allowed(action))
+    /// not permitted. `None` means that default permissions should be applied. Fails if the
+    /// user's permissions are not in effect at `entries_index`.
+    fn is_action_allowed_by_user(
+        &self,
+        user: &User,
+        action: Action,
+        entries_index: u64,
+    ) -> Result<Option<bool>> {
+        match self.permissions.get(user) {
+            Some(perms) => {
+                perms.validity.check(entries_index)?;
+                Ok(perms.is_allowed(action))
+            }
+            None => Ok(None),
+        }
     }
 }
 
@@ -300,33 +392,52 @@ pub struct PrivPermissions {
     pub permissions: BTreeMap<PublicKey, PrivUserPermissions>,
     /// The current index of the data when this permission change happened.
     pub entries_index: u64,
+    /// Identifier of the last entry when this permission change happened, if any. The
+    /// permissions are in effect for the entries placed after it.
+    pub entries_anchor: Option<EntryId>,
     /// The current index of the owners when this permission change happened.
     pub owners_index: u64,
 }
 
 pub trait Perm {
-    /// Returns true if `action` is allowed for the provided user.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()>;
+    /// Returns true if `action` is allowed for the provided user at `entries_index`.
+    fn is_action_allowed(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        entries_index: u64,
+    ) -> Result<()>;
     /// Gets the permissions for a user if applicable.
     fn user_permissions(&self, user: User) -> Option<UserPermissions>;
     /// Gets the last entry index.
     fn entries_index(&self) -> u64;
+    /// Gets the identifier of the last entry, if any.
+    fn entries_anchor(&self) -> Option<&EntryId>;
     /// Gets the last owner index.
     fn owners_index(&self) -> u64;
 }
 
 impl Perm for PubPermissions {
     /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
-    /// this action is not permitted.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
-        match self
-            .is_action_allowed_by_user(&User::Key(requester), action)
-            .or_else(|| self.is_action_allowed_by_user(&User::Anyone, action))
-        {
-            Some(true) => Ok(()),
-            Some(false) => Err(Error::AccessDenied),
-            None => Err(Error::AccessDenied),
+    /// this action is not permitted. A grant for the user which is not in effect is ignored in
+    /// favour of the permissions for `Anyone`, as for MutableData, and yields
+    /// `Err(PermissionExpired)` if those don't allow the action either and it has expired.
+    fn is_action_allowed(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        entries_index: u64,
+    ) -> Result<()> {
+        let mut error = Error::AccessDenied;
+        for user in &[User::Key(requester), User::Anyone] {
+            match self.is_action_allowed_by_user(user, action, entries_index) {
+                Ok(Some(true)) => return Ok(()),
+                Ok(Some(false)) => break,
+                Err(Error::PermissionExpired) => error = Error::PermissionExpired,
+                Ok(None) | Err(_) => (),
+            }
         }
+        Err(error)
     }
 
     /// Gets the permissions for a user if applicable.
@@ -341,6 +452,11 @@ impl Perm for PubPermissions {
         self.entries_index
     }
 
+    /// Returns the identifier of the last entry, if any.
+    fn entries_anchor(&self) -> Option<&EntryId> {
+        self.entries_anchor.as_ref()
+    }
+
     /// Returns the last owners index.
     fn owners_index(&self) -> u64 {
         self.owners_index
@@ -348,11 +464,18 @@ impl Perm for PubPermissions {
 }
 
 impl Perm for PrivPermissions {
-    /// Returns `Ok(())` if `action` is allowed for the provided user and `Err(AccessDenied)` if
-    /// this action is not permitted.
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+    /// Returns `Ok(())` if `action` is allowed for the provided user, `Err(AccessDenied)` if
+    /// this action is not permitted and `Err(PermissionExpired)` if the user's permissions are
+    /// not in effect anymore.
+    fn is_action_allowed(
+        &self,
+        requester: PublicKey,
+        action: Action,
+        entries_index: u64,
+    ) -> Result<()> {
         match self.permissions.get(&requester) {
             Some(perms) => {
+                perms.validity.check(entries_index)?;
                 if perms.is_allowed(action) {
                     Ok(())
                 } else {
@@ -379,6 +502,11 @@ impl Perm for PrivPermissions {
         self.entries_index
     }
 
+    /// Returns the identifier of the last entry, if any.
+    fn entries_anchor(&self) -> Option<&EntryId> {
+        self.entries_anchor.as_ref()
+    }
+
     /// Returns the last owners index.
     fn owners_index(&self) -> u64 {
         self.owners_index
@@ -10,13 +10,13 @@
 mod metadata;
 mod seq_crdt;
 
-use crate::{Error, PublicKey, Result, XorName};
+use crate::{utils, Error, OwnerPolicy, PublicKey, Result, Signature, XorName};
 pub use metadata::{
-    Action, Address, Entries, Entry, Index, Indices, Kind, Owner, Perm, Permissions,
+    Action, Address, Entries, Entry, EntryId, Index, Indices, Kind, Owner, Page, Perm, Permissions,
     PrivPermissions, PrivUserPermissions, PubPermissions, PubUserPermissions, User,
     UserPermissions,
 };
-use seq_crdt::{Op, SequenceCrdt};
+use seq_crdt::{Op, SequenceCrdt, SyncDelta, SyncSummary};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -44,6 +44,24 @@ impl Debug for PrivSeqData {
     }
 }
 
+/// Summary of the CRDT operations held by a Sequence replica.
+pub type Summary = SyncSummary<ActorType>;
+
+/// Checkpoint of the permissions and owners history of a Sequence at an entries index.
+pub type Checkpoint = seq_crdt::Checkpoint<ActorType>;
+
+/// Checkpoint of a Sequence signed by the owner in effect at its entries index.
+pub type SignedCheckpoint = seq_crdt::SignedCheckpoint<ActorType>;
+
+/// CRDT operations missing from a Sequence replica, as computed from its `Summary`.
+#[derive(Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Delta {
+    /// Delta for a Public Sequence.
+    Public(SyncDelta<ActorType, PubPermissions>),
+    /// Delta for a Private Sequence.
+    Private(SyncDelta<ActorType, PrivPermissions>),
+}
+
 /// Mutation operation to apply to Sequence.
 /// This is used for all kind of CRDT operationsmade on the Sequence,
 /// i.e. not only on the data but also on the permissions and owner info.
@@ -55,6 +73,33 @@ pub struct MutationOperation<T> {
     pub crdt_op: Op<T, ActorType>,
 }
 
+/// Mutation operation signed by its author.
+#[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Hash)]
+pub struct SignedOperation<T> {
+    /// The operation to apply.
+    pub op: MutationOperation<T>,
+    /// Public key of the author of the operation.
+    pub author: PublicKey,
+    /// Signature of the author over the bincode serialisation of `op`.
+    pub signature: Signature,
+}
+
+impl<T: Serialize> SignedOperation<T> {
+    /// Verifies that the operation was issued and signed by its author.
+    ///
+    /// Returns:
+    /// `Ok(())` if the signature is valid,
+    /// `Err::InvalidOperation` if the operation was issued by an actor other than the author,
+    /// `Err::InvalidSignature` if the signature is not valid.
+    pub fn verify(&self) -> Result<()> {
+        if self.op.crdt_op.dot().actor != self.author {
+            return Err(Error::InvalidOperation);
+        }
+        self.author
+            .verify(&self.signature, utils::serialise(&self.op))
+    }
+}
+
 /// Object storing a Sequence variant.
 #[derive(Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Data {
@@ -113,7 +158,9 @@ impl Data {
     /// Returns:
     /// `Ok(())` if the permissions are valid,
     /// `Err::InvalidOwners` if the last owner is invalid,
-    /// `Err::AccessDenied` if the action is not allowed.
+    /// `Err::AccessDenied` if the action is not allowed,
+    /// `Err::PermissionExpired` if the user's permissions are not in effect at the current
+    /// entries index anymore.
     pub fn check_permission(&self, action: Action, requester: PublicKey) -> Result<()> {
         macro_rules! check_perm {
             ($data: ident, $requester: ident, $action: ident) => {
@@ -121,7 +168,7 @@ impl Data {
                     $data
                         .permissions(Index::FromEnd(1))
                         .ok_or(Error::AccessDenied)?
-                        .is_action_allowed($requester, $action)
+                        .is_action_allowed($requester, $action, $data.entries_index())
                 })
             };
         }
@@ -137,6 +184,19 @@ impl Data {
         }
     }
 
+    /// Checks every entry against the owner and permissions which were in effect when it was
+    /// appended, and every permissions and owner change against the ones in effect before it.
+    ///
+    /// Returns:
+    /// `Ok(())` if all the entries, permissions and owners were authorised,
+    /// `Err::UnauthorisedEntries` with the index and author of every one which was not.
+    pub fn check_history(&self) -> Result<()> {
+        match self {
+            Data::Public(data) => data.check_history(),
+            Data::Private(data) => data.check_history(),
+        }
+    }
+
     /// Returns the last entry index.
     pub fn entries_index(&self) -> u64 {
         match self {
@@ -161,7 +221,9 @@ impl Data {
         }
     }
 
-    /// Gets a list of keys and values with the given indices.
+    /// Gets a list of keys and values with the given indices. Deleted entries are skipped.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
     pub fn in_range(&self, start: Index, end: Index) -> Option<Entries> {
         match self {
             Data::Public(data) => data.in_range(start, end),
@@ -169,7 +231,42 @@ impl Data {
         }
     }
 
-    /// Returns a value at 'index', if present.
+    /// Iterates over the entries with the given indices, without cloning them. Deleted entries
+    /// are skipped.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
+    pub fn iter_range(
+        &self,
+        start: Index,
+        end: Index,
+    ) -> Option<Box<dyn Iterator<Item = &Entry> + '_>> {
+        match self {
+            Data::Public(data) => Some(Box::new(data.iter_range(start, end)?)),
+            Data::Private(data) => Some(Box::new(data.iter_range(start, end)?)),
+        }
+    }
+
+    /// Gets a page of the entries with the given indices, holding at most `max_entries` entries
+    /// and `max_bytes` bytes of entries. Deleted entries are skipped.
+    ///
+    /// The page always holds at least one entry if the range isn't empty, so that paging
+    /// through the range always progresses.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
+    pub fn page(
+        &self,
+        start: Index,
+        end: Index,
+        max_entries: Option<u64>,
+        max_bytes: Option<u64>,
+    ) -> Option<Page> {
+        match self {
+            Data::Public(data) => data.page(start, end, max_entries, max_bytes),
+            Data::Private(data) => data.page(start, end, max_entries, max_bytes),
+        }
+    }
+
+    /// Returns a value at 'index', if present and not deleted.
     pub fn get(&self, index: Index) -> Option<&Vec<u8>> {
         match self {
             Data::Public(data) => data.get(index),
@@ -177,7 +274,7 @@ impl Data {
         }
     }
 
-    /// Returns the last entry, if present.
+    /// Returns the last entry which is not deleted, if present.
     pub fn last_entry(&self) -> Option<&Entry> {
         match self {
             Data::Public(data) => data.last_entry(),
@@ -193,6 +290,27 @@ impl Data {
         }
     }
 
+    /// Returns the owner currently in effect.
+    ///
+    /// Of concurrent ownership changes made from the same owners index, the first one in the
+    /// owners history takes effect, and the others, along with any ownership change made from
+    /// them, are stale.
+    pub fn current_owner(&self) -> Option<&Owner> {
+        match self {
+            Data::Public(data) => data.current_owner(),
+            Data::Private(data) => data.current_owner(),
+        }
+    }
+
+    /// Lists the concurrent ownership changes, keyed by the owners index they were made from.
+    /// The owners of each fork are in the order of the owners history.
+    pub fn owner_forks(&self) -> BTreeMap<u64, Vec<Owner>> {
+        match self {
+            Data::Public(data) => data.owner_forks(),
+            Data::Private(data) => data.owner_forks(),
+        }
+    }
+
     /// Appends new entry.
     pub fn append(&mut self, entry: Entry) -> MutationOperation<Entry> {
         let crdt_op = match self {
@@ -206,6 +324,51 @@ impl Data {
         }
     }
 
+    /// Inserts a new entry at `index`, shifting the following entries.
+    ///
+    /// Concurrent insertions at the same index are ordered deterministically on all replicas.
+    ///
+    /// Returns:
+    /// `Err::NoSuchEntry` if `index` is beyond the end of the data,
+    /// `Err::InvalidOperation` if `index` is before the entries index of the checkpoint.
+    pub fn insert_at(&mut self, index: Index, entry: Entry) -> Result<MutationOperation<Entry>> {
+        let crdt_op = match self {
+            Data::Public(data) => data.insert_at(index, entry),
+            Data::Private(data) => data.insert_at(index, entry),
+        }?;
+
+        Ok(MutationOperation {
+            address: *self.address(),
+            crdt_op,
+        })
+    }
+
+    /// Deletes the entry at `index`.
+    ///
+    /// The entry keeps its position in the data, so the indices of the other entries don't change.
+    ///
+    /// Returns `Err::NoSuchEntry` if there is no entry at `index` or if it's already deleted.
+    pub fn delete(&mut self, index: Index) -> Result<MutationOperation<Entry>> {
+        let crdt_op = match self {
+            Data::Public(data) => data.delete(index),
+            Data::Private(data) => data.delete(index),
+        }
+        .ok_or(Error::NoSuchEntry)?;
+
+        Ok(MutationOperation {
+            address: *self.address(),
+            crdt_op,
+        })
+    }
+
+    /// Returns `true` if the entry at `index` has been deleted.
+    pub fn is_deleted(&self, index: Index) -> bool {
+        match self {
+            Data::Public(data) => data.is_deleted(index),
+            Data::Private(data) => data.is_deleted(index),
+        }
+    }
+
     /// Apply CRDT operation.
     pub fn apply_crdt_op(&mut self, op: Op<Entry, ActorType>) {
         match self {
@@ -214,6 +377,25 @@ impl Data {
         };
     }
 
+    /// Applies a signed CRDT operation, after checking the signature and that the author is
+    /// allowed by the current owner and permissions to append the entry, or to delete it. An
+    /// inserted entry must also be allowed by the owner and permissions in effect at its position.
+    ///
+    /// Returns:
+    /// `Ok(())` if the operation was applied,
+    /// `Err::InvalidOperation` if the operation is not for this data or not issued by its author,
+    /// or if it inserts an entry before the entries index of the checkpoint,
+    /// `Err::InvalidSignature` if the signature is not valid,
+    /// `Err::AccessDenied` if the author was not allowed to append or delete.
+    pub fn apply_signed_op(&mut self, op: SignedOperation<Entry>) -> Result<()> {
+        verify_signed_op(self.address(), &op)?;
+        let SignedOperation { op, author, .. } = op;
+        match self {
+            Data::Public(data) => data.apply_checked_op(op.crdt_op, author),
+            Data::Private(data) => data.apply_checked_op(op.crdt_op, author),
+        }
+    }
+
     /// Adds a new permissions entry for Public Sequence.
     pub fn set_pub_permissions(
         &mut self,
@@ -224,6 +406,7 @@ impl Data {
             Data::Public(data) => {
                 let crdt_op = data.append_permissions(PubPermissions {
                     entries_index: data.entries_index(),
+                    entries_anchor: data.entries_anchor().cloned(),
                     owners_index: data.owners_index(),
                     permissions,
                 });
@@ -243,6 +426,7 @@ impl Data {
             Data::Private(data) => {
                 let crdt_op = data.append_permissions(PrivPermissions {
                     entries_index: data.entries_index(),
+                    entries_anchor: data.entries_anchor().cloned(),
                     owners_index: data.owners_index(),
                     permissions,
                 });
@@ -274,15 +458,60 @@ impl Data {
         }
     }
 
-    /// Adds a new owner entry.
-    pub fn set_owner(&mut self, owner: PublicKey) -> MutationOperation<Owner> {
+    /// Applies a signed Public Permissions CRDT operation, after checking the signature and that
+    /// the author was allowed to manage permissions at the position of the new permissions.
+    ///
+    /// Returns the same errors as `apply_signed_op`.
+    pub fn apply_signed_pub_perms_op(&mut self, op: SignedOperation<PubPermissions>) -> Result<()> {
+        verify_signed_op(self.address(), &op)?;
+        let SignedOperation { op, author, .. } = op;
+        match (self, &op.crdt_op) {
+            (Data::Public(data), Op::Insert { .. }) => {
+                data.apply_checked_perms_op(op.crdt_op, author)
+            }
+            _ => Err(Error::InvalidOperation),
+        }
+    }
+
+    /// Applies a signed Private Permissions CRDT operation, after checking the signature and
+    /// that the author was allowed to manage permissions at the position of the new permissions.
+    ///
+    /// Returns the same errors as `apply_signed_op`.
+    pub fn apply_signed_priv_perms_op(
+        &mut self,
+        op: SignedOperation<PrivPermissions>,
+    ) -> Result<()> {
+        verify_signed_op(self.address(), &op)?;
+        let SignedOperation { op, author, .. } = op;
+        match (self, &op.crdt_op) {
+            (Data::Private(data), Op::Insert { .. }) => {
+                data.apply_checked_perms_op(op.crdt_op, author)
+            }
+            _ => Err(Error::InvalidOperation),
+        }
+    }
+
+    /// Adds a new owner entry, succeeding the owners up to `owners_index`.
+    ///
+    /// Returns `Err::InvalidOwnersSuccessor` with the current owners index if `owners_index`
+    /// doesn't match it.
+    pub fn set_owner(
+        &mut self,
+        owner: impl Into<OwnerPolicy>,
+        owners_index: u64,
+    ) -> Result<MutationOperation<Owner>> {
+        let current_index = self.owners_index();
+        if owners_index != current_index {
+            return Err(Error::InvalidOwnersSuccessor(current_index));
+        }
+
         let address = *self.address();
         let crdt_op = match self {
-            Data::Public(data) => data.append_owner(owner),
-            Data::Private(data) => data.append_owner(owner),
+            Data::Public(data) => data.append_owner(owner.into()),
+            Data::Private(data) => data.append_owner(owner.into()),
         };
 
-        MutationOperation { address, crdt_op }
+        Ok(MutationOperation { address, crdt_op })
     }
 
     /// Apply Owner CRDT operation.
@@ -293,7 +522,22 @@ impl Data {
         };
     }
 
-    /// Checks if the requester is the last owner.
+    /// Applies a signed Owner CRDT operation, after checking the signature, that the new owner
+    /// isn't stale and that the author was the owner at the position of the new owner.
+    ///
+    /// Returns the same errors as `apply_signed_op`, plus `Err::InvalidOwners` if there was no
+    /// owner at that position, and `Err::InvalidOwnersSuccessor` with the current owners index if
+    /// a concurrent ownership change made from the same owners index is ordered before it.
+    pub fn apply_signed_owner_op(&mut self, op: SignedOperation<Owner>) -> Result<()> {
+        verify_signed_op(self.address(), &op)?;
+        let SignedOperation { op, author, .. } = op;
+        match self {
+            Data::Public(data) => data.apply_checked_owner_op(op.crdt_op, author),
+            Data::Private(data) => data.apply_checked_owner_op(op.crdt_op, author),
+        }
+    }
+
+    /// Checks if the requester can act as the current owner(s) on its own.
     ///
     /// Returns:
     /// `Ok(())` if the requester is the owner,
@@ -306,6 +550,91 @@ impl Data {
         }
     }
 
+    /// Checks if `signatures` of `message` are enough to act as the current owner(s), either as
+    /// a set of signatures of the owner keys or as a combined threshold signature.
+    ///
+    /// Returns:
+    /// `Ok(())` if enough owner keys signed,
+    /// `Err::InvalidOwners` if the last owner is invalid,
+    /// `Err::InvalidSignature` if the signature of an owner key is not valid,
+    /// `Err::AccessDenied` if not enough owner keys signed.
+    pub fn check_is_last_owner_with_signatures(
+        &self,
+        signatures: &BTreeMap<PublicKey, Signature>,
+        message: &[u8],
+    ) -> Result<()> {
+        match self {
+            Data::Public(data) => data.check_is_last_owner_with_signatures(signatures, message),
+            Data::Private(data) => data.check_is_last_owner_with_signatures(signatures, message),
+        }
+    }
+
+    /// Returns a summary of the CRDT operations held by this replica.
+    pub fn summary(&self) -> Summary {
+        match self {
+            Data::Public(data) => data.summary(),
+            Data::Private(data) => data.summary(),
+        }
+    }
+
+    /// Returns the CRDT operations held by this replica which are missing from the replica
+    /// `summary` was taken from.
+    pub fn delta_since(&self, summary: &Summary) -> Delta {
+        match self {
+            Data::Public(data) => Delta::Public(data.delta_since(summary)),
+            Data::Private(data) => Delta::Private(data.delta_since(summary)),
+        }
+    }
+
+    /// Applies all the CRDT operations of a delta.
+    ///
+    /// Returns `Err::InvalidOperation` if the delta is not of the same kind as the data.
+    pub fn apply_delta(&mut self, delta: Delta) -> Result<()> {
+        match (self, delta) {
+            (Data::Public(data), Delta::Public(delta)) => data.apply_delta(delta),
+            (Data::Private(data), Delta::Private(delta)) => data.apply_delta(delta),
+            _ => return Err(Error::InvalidOperation),
+        }
+        Ok(())
+    }
+
+    /// Returns the checkpoint the permissions and owners history has been folded into, if any.
+    pub fn checkpoint(&self) -> Option<&SignedCheckpoint> {
+        match self {
+            Data::Public(data) => data.checkpoint(),
+            Data::Private(data) => data.checkpoint(),
+        }
+    }
+
+    /// Takes a checkpoint of the permissions and owners history at `entries_index`, to be
+    /// signed by the owner in effect at that index and passed to `compact`.
+    ///
+    /// Returns `None` if `entries_index` is beyond the end of the data or before the current
+    /// checkpoint, or if this replica is missing permissions or owners operations.
+    pub fn take_checkpoint(&self, entries_index: u64) -> Option<Checkpoint> {
+        match self {
+            Data::Public(data) => data.take_checkpoint(entries_index),
+            Data::Private(data) => data.take_checkpoint(entries_index),
+        }
+    }
+
+    /// Folds the permissions and owners history into a signed checkpoint, discarding the
+    /// permissions and owners superseded before its entries index. Entries before the
+    /// checkpoint are no longer checked by `check_history`.
+    ///
+    /// Returns:
+    /// `Ok(())` if the history was folded,
+    /// `Err::InvalidOperation` if the checkpoint is not for this data, or doesn't match the
+    /// history held by this replica,
+    /// `Err::InvalidOwners` if there is no owner at the entries index of the checkpoint,
+    /// `Err::InvalidSignature` if the checkpoint is not signed by that owner.
+    pub fn compact(&mut self, checkpoint: SignedCheckpoint) -> Result<()> {
+        match self {
+            Data::Public(data) => data.compact(checkpoint),
+            Data::Private(data) => data.compact(checkpoint),
+        }
+    }
+
     /// Returns user permissions, if applicable.
     pub fn user_permissions(&self, user: User, index: impl Into<Index>) -> Result<UserPermissions> {
         let user_perm = match self {
@@ -355,20 +684,46 @@ impl From<PrivSeqData> for Data {
     }
 }
 
+// Private helpers
+
+fn verify_signed_op<T: Serialize>(address: &Address, op: &SignedOperation<T>) -> Result<()> {
+    if op.op.address != *address {
+        return Err(Error::InvalidOperation);
+    }
+    op.verify()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::Op;
     use crate::{
-        Error, PublicKey, Result, SData, SDataAddress, SDataIndex, SDataKind,
-        SDataPrivUserPermissions, SDataPubUserPermissions, SDataUser, SDataUserPermissions,
-        XorName,
+        ClientFullId, Error, HistoryIndex, OwnerPolicy, PublicKey, Result, SData, SDataAction,
+        SDataAddress, SDataDelta, SDataIndex, SDataKind, SDataMutationOperation, SDataPage,
+        SDataPrivUserPermissions, SDataPubUserPermissions, SDataSignedCheckpoint,
+        SDataSignedOperation, SDataUser, SDataUserPermissions, XorName,
     };
+    use crdts::Dot;
+    use serde::Serialize;
     use std::collections::BTreeMap;
     use threshold_crypto::SecretKey;
+    use unwrap::unwrap;
 
     fn gen_public_key() -> PublicKey {
         PublicKey::Bls(SecretKey::random().public_key())
     }
 
+    fn sign<T: Serialize>(
+        full_id: &ClientFullId,
+        op: SDataMutationOperation<T>,
+    ) -> SDataSignedOperation<T> {
+        let signature = full_id.sign(unwrap!(bincode::serialize(&op)));
+        SDataSignedOperation {
+            op,
+            author: *full_id.public_id().public_key(),
+            signature,
+        }
+    }
+
     #[test]
     fn sequence_create_public() {
         let actor = gen_public_key();
@@ -446,11 +801,11 @@ mod tests {
         let mut replica2 = SData::new_pub(actor, sdata_name, sdata_tag);
 
         let mut perms1 = BTreeMap::default();
-        let user_perms1 = SDataPubUserPermissions::new(true, false);
+        let user_perms1 = SDataPubUserPermissions::new(true, false, false);
         let _ = perms1.insert(SDataUser::Anyone, user_perms1);
 
         let mut perms2 = BTreeMap::default();
-        let user_perms2 = SDataPubUserPermissions::new(false, true);
+        let user_perms2 = SDataPubUserPermissions::new(false, false, true);
         let _ = perms2.insert(SDataUser::Key(actor), user_perms2);
 
         let op1 = replica1.set_pub_permissions(perms1.clone())?;
@@ -498,11 +853,11 @@ mod tests {
         let mut replica2 = SData::new_priv(actor2, sdata_name, sdata_tag);
 
         let mut perms1 = BTreeMap::default();
-        let user_perms1 = SDataPrivUserPermissions::new(true, false, true);
+        let user_perms1 = SDataPrivUserPermissions::new(true, false, false, true);
         let _ = perms1.insert(actor1, user_perms1);
 
         let mut perms2 = BTreeMap::default();
-        let user_perms2 = SDataPrivUserPermissions::new(false, true, false);
+        let user_perms2 = SDataPrivUserPermissions::new(false, true, false, false);
         let _ = perms2.insert(actor2, user_perms2);
 
         let op1 = replica1.set_priv_permissions(perms1.clone())?;
@@ -550,8 +905,8 @@ mod tests {
 
         let owner1 = gen_public_key();
         let owner2 = gen_public_key();
-        let op1 = replica1.set_owner(owner1);
-        let op2 = replica1.set_owner(owner2);
+        let op1 = replica1.set_owner(owner1, 0)?;
+        let op2 = replica1.set_owner(owner2, 1)?;
 
         // we apply the operations in different order, to verify that doesn't affect the result
         replica2.apply_crdt_owner_op(op2.crdt_op);
@@ -562,7 +917,7 @@ mod tests {
 
         let index_0 = SDataIndex::FromStart(0);
         let first_entry = replica1.owner(index_0).ok_or(Error::InvalidOwners)?;
-        assert_eq!(first_entry.public_key, owner1);
+        assert_eq!(first_entry.policy, OwnerPolicy::Key(owner1));
         assert_eq!(first_entry.entries_index, 0);
         assert_eq!(first_entry.permissions_index, 0);
         assert_eq!(
@@ -572,7 +927,7 @@ mod tests {
 
         let index_1 = SDataIndex::FromStart(1);
         let second_entry = replica1.owner(index_1).ok_or(Error::InvalidOwners)?;
-        assert_eq!(second_entry.public_key, owner2);
+        assert_eq!(second_entry.policy, OwnerPolicy::Key(owner2));
         assert_eq!(second_entry.entries_index, 0);
         assert_eq!(second_entry.permissions_index, 0);
         assert_eq!(
@@ -585,4 +940,753 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn sequence_delta_sync() -> Result<()> {
+        let actor1 = gen_public_key();
+        let actor2 = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica1 = SData::new_pub(actor1, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(actor2, sdata_name, sdata_tag);
+
+        let op1 = replica1.append(b"value0".to_vec());
+        let _ = replica1.append(b"value1".to_vec());
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            SDataUser::Anyone,
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let _ = replica1.set_pub_permissions(perms)?;
+        let _ = replica1.set_owner(actor1, 0)?;
+
+        // replica2 already has the first entry and one of its own
+        replica2.apply_crdt_op(op1.crdt_op);
+        let op3 = replica2.append(b"value2".to_vec());
+
+        let delta = replica1.delta_since(&replica2.summary());
+        match delta {
+            SDataDelta::Public(ref delta) => {
+                assert_eq!(delta.data.len(), 1);
+                assert_eq!(delta.permissions.len(), 1);
+                assert_eq!(delta.owners.len(), 1);
+            }
+            SDataDelta::Private(_) => panic!("Unexpected private delta"),
+        }
+        replica2.apply_delta(delta)?;
+        replica1.apply_crdt_op(op3.crdt_op);
+
+        assert_eq!(replica2.entries_index(), 3);
+        assert_eq!(replica2.permissions_index(), 1);
+        assert_eq!(replica2.owners_index(), 1);
+        let all = (SDataIndex::FromStart(0), SDataIndex::FromEnd(0));
+        assert_eq!(
+            replica1.in_range(all.0, all.1),
+            replica2.in_range(all.0, all.1)
+        );
+        assert_eq!(replica1.summary(), replica2.summary());
+
+        let empty_delta = replica1.delta_since(&replica2.summary());
+        match empty_delta {
+            SDataDelta::Public(ref delta) => {
+                assert!(delta.data.is_empty());
+                assert!(delta.permissions.is_empty());
+                assert!(delta.owners.is_empty());
+            }
+            SDataDelta::Private(_) => panic!("Unexpected private delta"),
+        }
+
+        let mut priv_replica = SData::new_priv(actor1, sdata_name, sdata_tag);
+        assert_eq!(
+            priv_replica.apply_delta(empty_delta),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_apply_signed_ops() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner = ClientFullId::new_bls(&mut rng);
+        let owner_key = *owner.public_id().public_key();
+        let writer = ClientFullId::new_ed25519(&mut rng);
+        let writer_key = *writer.public_id().public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+
+        let mut replica1 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let mut writer_replica = SData::new_pub(writer_key, sdata_name, sdata_tag);
+
+        // the first owner is part of the stored data, it can't be set by a signed op
+        let owner_op = sign(&owner, replica1.set_owner(owner_key, 0)?);
+        assert_eq!(
+            replica2.apply_signed_owner_op(owner_op.clone()),
+            Err(Error::InvalidOwners)
+        );
+        replica2.apply_crdt_owner_op(owner_op.op.crdt_op);
+
+        // only the owner can append until permissions are granted
+        let entry_op = sign(&writer, writer_replica.append(b"value0".to_vec()));
+        assert_eq!(
+            replica2.apply_signed_op(entry_op.clone()),
+            Err(Error::AccessDenied)
+        );
+
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            SDataUser::Key(writer_key),
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let perms_op = sign(&owner, replica1.set_pub_permissions(perms)?);
+        replica2.apply_signed_pub_perms_op(perms_op)?;
+        replica2.apply_signed_op(entry_op)?;
+        assert_eq!(replica2.last_entry(), Some(&b"value0".to_vec()));
+
+        // the writer can't manage permissions
+        let perms_op = sign(
+            &writer,
+            writer_replica.set_pub_permissions(BTreeMap::new())?,
+        );
+        assert_eq!(
+            replica2.apply_signed_pub_perms_op(perms_op),
+            Err(Error::AccessDenied)
+        );
+
+        // the op must be issued by its author and signed by it
+        let mut forged_op = sign(&writer, replica1.append(b"value1".to_vec()));
+        assert_eq!(
+            replica2.apply_signed_op(forged_op.clone()),
+            Err(Error::InvalidOperation)
+        );
+        forged_op.author = owner_key;
+        assert_eq!(
+            replica2.apply_signed_op(forged_op),
+            Err(Error::SigningKeyTypeMismatch)
+        );
+        assert_eq!(replica2.entries_index(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_reject_signed_deletions() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner1 = ClientFullId::new_bls(&mut rng);
+        let owner1_key = *owner1.public_id().public_key();
+        let owner2_key = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+
+        let mut replica1 = SData::new_priv(owner1_key, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_priv(owner1_key, sdata_name, sdata_tag);
+        replica2.apply_crdt_owner_op(replica1.set_owner(owner1_key, 0)?.crdt_op);
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            owner2_key,
+            SDataPrivUserPermissions::new(true, false, false, false),
+        );
+        let perms_op = sign(&owner1, replica1.set_priv_permissions(perms)?);
+        replica2.apply_signed_priv_perms_op(perms_op.clone())?;
+        let owner_op = sign(&owner1, replica1.set_owner(owner2_key, 1)?);
+        replica2.apply_signed_owner_op(owner_op.clone())?;
+
+        // the former owner can't delete the owner entry of its successor
+        let (id, remote) = match owner_op.op.crdt_op {
+            Op::Insert { id, dot, .. } => (id, dot),
+            Op::Delete { .. } => panic!("unexpected deletion"),
+        };
+        let del_op = sign(
+            &owner1,
+            SDataMutationOperation {
+                address: *replica2.address(),
+                crdt_op: Op::Delete {
+                    id,
+                    remote,
+                    dot: Dot::new(owner1_key, 3),
+                },
+            },
+        );
+        assert_eq!(
+            replica2.apply_signed_owner_op(del_op),
+            Err(Error::InvalidOperation)
+        );
+        replica2.check_is_last_owner(owner2_key)?;
+        assert_eq!(
+            replica2.check_is_last_owner(owner1_key),
+            Err(Error::AccessDenied)
+        );
+
+        // nor the permissions it set
+        let (id, remote) = match perms_op.op.crdt_op {
+            Op::Insert { id, dot, .. } => (id, dot),
+            Op::Delete { .. } => panic!("unexpected deletion"),
+        };
+        let del_op = sign(
+            &owner1,
+            SDataMutationOperation {
+                address: *replica2.address(),
+                crdt_op: Op::Delete {
+                    id,
+                    remote,
+                    dot: Dot::new(owner1_key, 4),
+                },
+            },
+        );
+        assert_eq!(
+            replica2.apply_signed_priv_perms_op(del_op),
+            Err(Error::InvalidOperation)
+        );
+        assert_eq!(replica2.permissions_index(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_check_history() -> Result<()> {
+        let owner = gen_public_key();
+        let writer = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_pub(owner, sdata_name, sdata_tag);
+        let mut writer_replica = SData::new_pub(writer, sdata_name, sdata_tag);
+
+        let _ = replica.set_owner(owner, 0)?;
+        writer_replica.apply_crdt_op(replica.append(b"value0".to_vec()).crdt_op);
+        replica.check_history()?;
+
+        // the writer appends before being granted permissions
+        let op = writer_replica.append(b"value1".to_vec());
+        replica.apply_crdt_op(op.crdt_op);
+
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            SDataUser::Key(writer),
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let _ = replica.set_pub_permissions(perms)?;
+        writer_replica.apply_crdt_op(replica.append(b"value2".to_vec()).crdt_op);
+        let op = writer_replica.append(b"value3".to_vec());
+        replica.apply_crdt_op(op.crdt_op);
+
+        let mut expected = BTreeMap::new();
+        let _ = expected.insert(HistoryIndex::Entry(1), writer);
+        assert_eq!(
+            replica.check_history(),
+            Err(Error::UnauthorisedEntries(expected.clone()))
+        );
+
+        // the writer changes the permissions and the owner without being allowed to
+        writer_replica.apply_delta(replica.delta_since(&writer_replica.summary()))?;
+        let perms_op = writer_replica.set_pub_permissions(BTreeMap::new())?;
+        replica.apply_crdt_pub_perms_op(perms_op.crdt_op)?;
+        let owner_op = writer_replica.set_owner(writer, 1)?;
+        replica.apply_crdt_owner_op(owner_op.crdt_op);
+        let _ = expected.insert(HistoryIndex::Permissions(1), writer);
+        let _ = expected.insert(HistoryIndex::Owner(1), writer);
+        assert_eq!(
+            replica.check_history(),
+            Err(Error::UnauthorisedEntries(expected))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_delete_entry_and_apply() -> Result<()> {
+        let actor = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica1 = SData::new_pub(actor, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(actor, sdata_name, sdata_tag);
+        let mut replica3 = SData::new_pub(actor, sdata_name, sdata_tag);
+
+        let entry1 = b"value0".to_vec();
+        let entry2 = b"value1".to_vec();
+        let entry3 = b"value2".to_vec();
+        let op1 = replica1.append(entry1.clone());
+        let op2 = replica1.append(entry2.clone());
+        let op3 = replica1.append(entry3.clone());
+
+        let index_1 = SDataIndex::FromStart(1);
+        let del_op = replica1.delete(index_1)?;
+        assert_eq!(replica1.delete(index_1).err(), Some(Error::NoSuchEntry));
+
+        // the deletion is applied before the insertion it refers to
+        replica2.apply_crdt_op(del_op.crdt_op);
+        replica2.apply_crdt_op(op3.crdt_op);
+        replica2.apply_crdt_op(op2.crdt_op);
+        replica2.apply_crdt_op(op1.crdt_op);
+
+        for replica in &[&replica1, &replica2] {
+            assert_eq!(replica.entries_index(), 3);
+            assert!(replica.is_deleted(index_1));
+            assert_eq!(replica.get(index_1), None);
+            assert_eq!(replica.get(SDataIndex::FromStart(2)), Some(&entry3));
+            assert_eq!(
+                replica.in_range(SDataIndex::FromStart(0), SDataIndex::FromEnd(0)),
+                Some(vec![entry1.clone(), entry3.clone()])
+            );
+        }
+
+        let del_op = replica1.delete(SDataIndex::FromEnd(1))?;
+        assert_eq!(replica1.last_entry(), Some(&entry1));
+        assert_eq!(replica1.entries_index(), 3);
+
+        // the deletions are synced as well
+        replica3.apply_delta(replica1.delta_since(&replica3.summary()))?;
+        assert_eq!(replica3.last_entry(), Some(&entry1));
+        assert!(replica3.is_deleted(index_1));
+        assert_eq!(replica3.summary(), replica1.summary());
+
+        replica2.apply_crdt_op(del_op.crdt_op);
+        assert_eq!(replica2.summary(), replica1.summary());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_delete_permissions() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner = ClientFullId::new_bls(&mut rng);
+        let owner_key = *owner.public_id().public_key();
+        let writer = ClientFullId::new_bls(&mut rng);
+        let writer_key = *writer.public_id().public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_priv(owner_key, sdata_name, sdata_tag);
+        let mut writer_replica = SData::new_priv(writer_key, sdata_name, sdata_tag);
+
+        let _ = replica.set_owner(owner_key, 0)?;
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            writer_key,
+            SDataPrivUserPermissions::new(true, true, false, false),
+        );
+        let _ = replica.set_priv_permissions(perms.clone())?;
+        writer_replica.apply_crdt_op(replica.append(b"value0".to_vec()).crdt_op);
+        writer_replica.apply_crdt_op(replica.append(b"value1".to_vec()).crdt_op);
+
+        let del_op = sign(&writer, writer_replica.delete(SDataIndex::FromStart(0))?);
+        assert_eq!(
+            replica.apply_signed_op(del_op.clone()),
+            Err(Error::AccessDenied)
+        );
+
+        let _ = perms.insert(
+            writer_key,
+            SDataPrivUserPermissions::new(true, true, true, false),
+        );
+        let _ = replica.set_priv_permissions(perms)?;
+        replica.apply_signed_op(del_op)?;
+        assert!(replica.is_deleted(SDataIndex::FromStart(0)));
+
+        writer_replica.apply_delta(replica.delta_since(&writer_replica.summary()))?;
+        let del_op = sign(&owner, replica.delete(SDataIndex::FromStart(1))?);
+        writer_replica.apply_signed_op(del_op)?;
+        assert!(writer_replica.is_deleted(SDataIndex::FromStart(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_insert_at_and_apply() -> Result<()> {
+        let actor1 = gen_public_key();
+        let actor2 = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica1 = SData::new_pub(actor1, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(actor2, sdata_name, sdata_tag);
+
+        let op1 = replica1.append(b"first".to_vec());
+        let op2 = replica1.append(b"last".to_vec());
+        replica2.apply_crdt_op(op1.crdt_op);
+        replica2.apply_crdt_op(op2.crdt_op);
+
+        // both replicas insert concurrently at the same position
+        let op3 = replica1.insert_at(SDataIndex::FromStart(1), b"middle1".to_vec())?;
+        let op4 = replica2.insert_at(SDataIndex::FromEnd(1), b"middle2".to_vec())?;
+        replica1.apply_crdt_op(op4.crdt_op);
+        replica2.apply_crdt_op(op3.crdt_op);
+
+        let all = (SDataIndex::FromStart(0), SDataIndex::FromEnd(0));
+        let entries = replica1.in_range(all.0, all.1).ok_or(Error::NoSuchEntry)?;
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], b"first".to_vec());
+        assert_eq!(entries[3], b"last".to_vec());
+        assert_eq!(Some(entries), replica2.in_range(all.0, all.1));
+
+        let op5 = replica1.insert_at(SDataIndex::FromStart(0), b"new first".to_vec())?;
+        replica2.apply_crdt_op(op5.crdt_op);
+        assert_eq!(
+            replica2.get(SDataIndex::FromStart(0)),
+            Some(&b"new first".to_vec())
+        );
+        assert_eq!(replica2.entries_index(), 5);
+
+        assert_eq!(
+            replica1
+                .insert_at(SDataIndex::FromStart(6), b"out of bounds".to_vec())
+                .err(),
+            Some(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_checked_insert_at() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner = ClientFullId::new_bls(&mut rng);
+        let owner_key = *owner.public_id().public_key();
+        let writer = ClientFullId::new_bls(&mut rng);
+        let writer_key = *writer.public_id().public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let mut writer_replica = SData::new_pub(writer_key, sdata_name, sdata_tag);
+
+        let _ = replica.set_owner(owner_key, 0)?;
+        let _ = replica.append(b"value0".to_vec());
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            SDataUser::Key(writer_key),
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let _ = replica.set_pub_permissions(perms)?;
+        writer_replica.apply_delta(replica.delta_since(&writer_replica.summary()))?;
+        replica.apply_signed_op(sign(&writer, writer_replica.append(b"value1".to_vec())))?;
+
+        // the writer can't insert before being granted permissions either
+        let op = sign(
+            &writer,
+            writer_replica.insert_at(SDataIndex::FromStart(0), b"first".to_vec())?,
+        );
+        assert_eq!(replica.apply_signed_op(op), Err(Error::AccessDenied));
+
+        // once revoked, the writer can neither insert before the revocation nor append
+        let _ = replica.set_pub_permissions(BTreeMap::new())?;
+        let op = sign(
+            &writer,
+            writer_replica.insert_at(SDataIndex::FromStart(1), b"inserted".to_vec())?,
+        );
+        assert_eq!(replica.apply_signed_op(op), Err(Error::AccessDenied));
+        let op = sign(&writer, writer_replica.append(b"appended".to_vec()));
+        assert_eq!(replica.apply_signed_op(op), Err(Error::AccessDenied));
+
+        // the owner can insert before the entries the permissions were changed at
+        let mut replica2 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let _ = replica.append(b"value2".to_vec());
+        replica2.apply_delta(replica.delta_since(&replica2.summary()))?;
+        let op = sign(
+            &owner,
+            replica.insert_at(SDataIndex::FromStart(0), b"first".to_vec())?,
+        );
+        replica2.apply_signed_op(op)?;
+        assert_eq!(
+            replica2.get(SDataIndex::FromStart(0)),
+            Some(&b"first".to_vec())
+        );
+        // the writer's entry, now at the index the permissions were revoked at, is still
+        // checked against the permissions granting it
+        replica.check_history()?;
+        replica2.check_history()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_range_pages() -> Result<()> {
+        let actor = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_pub(actor, sdata_name, sdata_tag);
+
+        let empty = (SDataIndex::FromStart(0), SDataIndex::FromEnd(0));
+        assert_eq!(replica.in_range(empty.0, empty.1), Some(vec![]));
+        assert_eq!(
+            replica.page(empty.0, empty.1, Some(1), None),
+            Some(SDataPage {
+                entries: vec![],
+                next: None,
+            })
+        );
+
+        for i in 0..5 {
+            let _ = replica.append(vec![i; 10]);
+        }
+        let _ = replica.delete(SDataIndex::FromStart(1))?;
+
+        let all = (SDataIndex::FromStart(0), SDataIndex::FromEnd(0));
+        let entries = replica.in_range(all.0, all.1).ok_or(Error::NoSuchEntry)?;
+        assert_eq!(entries.len(), 4);
+        assert!(replica
+            .iter_range(all.0, all.1)
+            .ok_or(Error::NoSuchEntry)?
+            .eq(entries.iter()));
+
+        // page through the range, two entries at a time
+        let page = replica
+            .page(all.0, all.1, Some(2), None)
+            .ok_or(Error::NoSuchEntry)?;
+        assert_eq!(page.entries, vec![vec![0; 10], vec![2; 10]]);
+        assert_eq!(page.next, Some(SDataIndex::FromStart(3)));
+        let page = replica
+            .page(SDataIndex::FromStart(3), all.1, Some(2), None)
+            .ok_or(Error::NoSuchEntry)?;
+        assert_eq!(page.entries, vec![vec![3; 10], vec![4; 10]]);
+        assert_eq!(page.next, None);
+
+        // the byte budget limits the page, but a page always holds at least one entry
+        let page = replica
+            .page(all.0, all.1, None, Some(25))
+            .ok_or(Error::NoSuchEntry)?;
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.next, Some(SDataIndex::FromStart(3)));
+        let page = replica
+            .page(all.0, all.1, None, Some(5))
+            .ok_or(Error::NoSuchEntry)?;
+        assert_eq!(page.entries, vec![vec![0; 10]]);
+        assert_eq!(page.next, Some(SDataIndex::FromStart(2)));
+
+        assert_eq!(
+            replica.in_range(SDataIndex::FromStart(0), SDataIndex::FromStart(6)),
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_compact_history() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner = ClientFullId::new_ed25519(&mut rng);
+        let owner_key = *owner.public_id().public_key();
+        let writer = ClientFullId::new_ed25519(&mut rng);
+        let writer_key = *writer.public_id().public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica1 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+
+        let _ = replica1.set_owner(owner_key, 0)?;
+        let mut perms = BTreeMap::default();
+        let _ = perms.insert(
+            SDataUser::Key(writer_key),
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let _ = replica1.set_pub_permissions(perms.clone())?;
+        let _ = replica1.append(b"value0".to_vec());
+        let _ = replica1.append(b"value1".to_vec());
+        let _ = replica1.set_pub_permissions(perms)?;
+        let _ = replica1.append(b"value2".to_vec());
+        replica2.apply_delta(replica1.delta_since(&replica2.summary()))?;
+
+        assert!(replica1.take_checkpoint(4).is_none());
+        let checkpoint = replica1.take_checkpoint(3).ok_or(Error::InvalidOperation)?;
+        assert_eq!(checkpoint.permissions_index, 1);
+        assert_eq!(checkpoint.owners_index, 0);
+
+        // the checkpoint must be signed by the owner
+        let serialised_checkpoint = unwrap!(bincode::serialize(&checkpoint));
+        let mut signed_checkpoint = SDataSignedCheckpoint {
+            checkpoint,
+            signatures: BTreeMap::new(),
+        };
+        let _ = signed_checkpoint
+            .signatures
+            .insert(writer_key, writer.sign(&serialised_checkpoint));
+        assert_eq!(
+            replica1.compact(signed_checkpoint.clone()),
+            Err(Error::AccessDenied)
+        );
+        let _ = signed_checkpoint
+            .signatures
+            .insert(owner_key, writer.sign(&serialised_checkpoint));
+        assert_eq!(
+            replica1.compact(signed_checkpoint.clone()),
+            Err(Error::InvalidSignature)
+        );
+        let _ = signed_checkpoint
+            .signatures
+            .insert(owner_key, owner.sign(&serialised_checkpoint));
+        replica1.compact(signed_checkpoint.clone())?;
+
+        assert_eq!(replica1.checkpoint(), Some(&signed_checkpoint));
+        assert_eq!(replica1.permissions_index(), 2);
+        assert_eq!(replica1.pub_permissions(0).err(), Some(Error::NoSuchEntry));
+        assert!(replica1.pub_permissions(1).is_ok());
+        assert_eq!(replica1.owners_index(), 1);
+        assert!(replica1.owner(0).is_some());
+        replica1.check_history()?;
+        assert_eq!(replica1.summary(), replica2.summary());
+
+        // the folded operations are not brought back by an uncompacted replica
+        replica1.apply_delta(
+            replica2.delta_since(&SData::new_pub(owner_key, sdata_name, sdata_tag).summary()),
+        )?;
+        assert_eq!(replica1.permissions_index(), 2);
+        assert_eq!(replica1.pub_permissions(0).err(), Some(Error::NoSuchEntry));
+
+        // later operations are still verified against the history kept
+        let perms_op = sign(&owner, replica2.set_pub_permissions(BTreeMap::new())?);
+        replica1.apply_signed_pub_perms_op(perms_op)?;
+        assert_eq!(replica1.permissions_index(), 3);
+        let entry_op = sign(&owner, replica2.append(b"value3".to_vec()));
+        replica1.apply_signed_op(entry_op)?;
+        replica1.check_history()?;
+
+        replica2.compact(signed_checkpoint)?;
+        assert_eq!(replica1.summary(), replica2.summary());
+        assert_eq!(replica2.permissions_index(), 3);
+
+        // entries can't be inserted before the checkpoint anymore
+        assert_eq!(
+            replica2
+                .insert_at(SDataIndex::FromStart(2), b"folded".to_vec())
+                .err(),
+            Some(Error::InvalidOperation)
+        );
+        let _ = replica2.insert_at(SDataIndex::FromStart(3), b"inserted".to_vec())?;
+        replica2.check_history()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_concurrent_owner_transfers() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner = ClientFullId::new_ed25519(&mut rng);
+        let owner_key = *owner.public_id().public_key();
+        let actor2 = ClientFullId::new_ed25519(&mut rng);
+        let actor2_key = *actor2.public_id().public_key();
+        let new_owner1 = gen_public_key();
+        let new_owner2 = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica1 = SData::new_pub(owner_key, sdata_name, sdata_tag);
+        let mut replica2 = SData::new_pub(actor2_key, sdata_name, sdata_tag);
+        let mut replica3 = SData::new_pub(actor2_key, sdata_name, sdata_tag);
+
+        let op = replica1.set_owner(owner_key, 0)?;
+        replica2.apply_crdt_owner_op(op.crdt_op.clone());
+        replica3.apply_crdt_owner_op(op.crdt_op);
+        assert_eq!(
+            replica1.set_owner(new_owner1, 0).err(),
+            Some(Error::InvalidOwnersSuccessor(1))
+        );
+
+        // both replicas transfer the ownership concurrently from the same owners index
+        let op1 = sign(&owner, replica1.set_owner(new_owner1, 1)?);
+        let op2 = replica2.set_owner(new_owner2, 1)?;
+        replica1.apply_crdt_owner_op(op2.crdt_op.clone());
+        replica2.apply_crdt_owner_op(op1.op.crdt_op.clone());
+
+        let forks = replica1.owner_forks();
+        assert_eq!(forks, replica2.owner_forks());
+        assert_eq!(forks.len(), 1);
+        let fork = forks.get(&1).ok_or(Error::NoSuchEntry)?;
+        assert_eq!(fork.len(), 2);
+
+        // the first transfer in the owners history wins on every replica
+        for replica in &[&replica1, &replica2] {
+            assert_eq!(replica.owners_index(), 3);
+            assert_eq!(replica.current_owner(), Some(&fork[0]));
+        }
+
+        // the order of concurrent transfers is arbitrary but the same on every replica: a
+        // transfer ordered after a concurrent one is stale
+        let op2 = sign(&actor2, op2);
+        let (first, second) = if fork[0].policy.is_satisfied_by(&new_owner1) {
+            (op1, op2)
+        } else {
+            (op2, op1)
+        };
+        replica3.apply_crdt_owner_op(first.op.crdt_op);
+        assert_eq!(
+            replica3.apply_signed_owner_op(second),
+            Err(Error::InvalidOwnersSuccessor(2))
+        );
+        assert_eq!(replica3.current_owner(), Some(&fork[0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_multiple_owners() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let owner1 = ClientFullId::new_ed25519(&mut rng);
+        let owner1_key = *owner1.public_id().public_key();
+        let owner2 = ClientFullId::new_bls(&mut rng);
+        let owner2_key = *owner2.public_id().public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_pub(owner1_key, sdata_name, sdata_tag);
+
+        let keys = vec![owner1_key, owner2_key].into_iter().collect();
+        let _ = replica.set_owner(OwnerPolicy::new_keys(keys, 2)?, 0)?;
+
+        // no single owner can act on its own
+        assert_eq!(
+            replica.check_is_last_owner(owner1_key),
+            Err(Error::AccessDenied)
+        );
+
+        let message = b"message";
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(owner1_key, owner1.sign(message));
+        assert_eq!(
+            replica.check_is_last_owner_with_signatures(&signatures, message),
+            Err(Error::AccessDenied)
+        );
+        let _ = signatures.insert(owner2_key, owner2.sign(message));
+        replica.check_is_last_owner_with_signatures(&signatures, message)
+    }
+
+    #[test]
+    fn sequence_expiring_permissions() -> Result<()> {
+        let owner = gen_public_key();
+        let writer = gen_public_key();
+        let sdata_name: XorName = rand::random();
+        let sdata_tag = 43_000;
+        let mut replica = SData::new_pub(owner, sdata_name, sdata_tag);
+
+        let mut perms = BTreeMap::default();
+        let user_perms = SDataPubUserPermissions::new(true, false, false)
+            .valid_from(1)
+            .valid_until(2);
+        let _ = perms.insert(SDataUser::Key(writer), user_perms);
+        let _ = perms.insert(
+            SDataUser::Anyone,
+            SDataPubUserPermissions::new(false, false, false),
+        );
+        let _ = replica.set_pub_permissions(perms.clone())?;
+
+        // not in effect yet
+        assert_eq!(
+            replica.check_permission(SDataAction::Append, writer),
+            Err(Error::AccessDenied)
+        );
+
+        let _ = replica.append(b"entry".to_vec());
+        replica.check_permission(SDataAction::Append, writer)?;
+        let _ = replica.append(b"entry".to_vec());
+        replica.check_permission(SDataAction::Append, writer)?;
+
+        // expired
+        let _ = replica.append(b"entry".to_vec());
+        assert_eq!(
+            replica.check_permission(SDataAction::Append, writer),
+            Err(Error::PermissionExpired)
+        );
+
+        // falls back to the permissions for `Anyone`
+        let _ = perms.insert(
+            SDataUser::Anyone,
+            SDataPubUserPermissions::new(true, false, false),
+        );
+        let _ = replica.set_pub_permissions(perms)?;
+        replica.check_permission(SDataAction::Append, writer)
+    }
 }
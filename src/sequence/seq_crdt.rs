@@ -7,12 +7,18 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::metadata::{Address, Entries, Entry, Index, Indices, Owner, Perm};
-use crate::{Error, PublicKey, Result};
-use crdts::{lseq::LSeq, CmRDT};
+use super::metadata::{
+    Action, Address, Entries, Entry, EntryId, Index, Indices, Owner, Page, Perm,
+};
+use crate::{utils, Error, HistoryIndex, OwnerPolicy, PublicKey, Result, Signature};
 pub use crdts::{lseq::Op, Actor};
+use crdts::{
+    lseq::{ident::Identifier, LSeq},
+    CmRDT, Dot, VClock,
+};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Display},
     hash::Hash,
 };
@@ -39,7 +45,83 @@ where
     permissions: LSeq<P, A>,
     /// This is the history of owners, with each entry representing an owner. Each single owner
     /// could represent an individual user, or a group of users, depending on the `PublicKey` type.
+    ///
+    /// An owner is only in effect if its `owners_index` is its own index in the history, i.e. if
+    /// it was appended after all the owners before it. Of concurrent transfers made from the same
+    /// owners index, the first one in the history takes effect, and the others are stale.
     owners: LSeq<Owner, A>,
+    /// Tombstones of the deleted entries. Deleted entries are kept in the data so the entries the
+    /// permissions and owners history is anchored to remain in it.
+    tombstones: BTreeMap<Identifier<A>, Tombstone<A>>,
+    /// Checkpoint the permissions and owners history has been folded into, if any.
+    checkpoint: Option<SignedCheckpoint<A>>,
+}
+
+/// Tombstone of a deleted entry.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
+struct Tombstone<A: Actor> {
+    /// Dot of the insertion of the entry.
+    remote: Dot<A>,
+    /// Dots of the deletions of the entry, as it could be deleted concurrently by several actors.
+    dots: Vec<Dot<A>>,
+}
+
+/// Summary of the CRDT operations held by a replica, for each of the data, permissions and owners.
+///
+/// Each clock only accounts for the operations held without gaps, so a delta computed against it
+/// never misses an operation the replica didn't receive.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Debug)]
+pub struct SyncSummary<A: Actor> {
+    /// Clock of the data operations.
+    pub data: VClock<A>,
+    /// Clock of the permissions operations.
+    pub permissions: VClock<A>,
+    /// Clock of the owners operations.
+    pub owners: VClock<A>,
+}
+
+/// Checkpoint of the permissions and owners history of a Sequence at an entries index.
+///
+/// The permissions and owners superseded before the entries index are folded into the
+/// checkpoint, so they can be discarded. The ones in effect at the entries index are kept, so
+/// the later entries, permissions and owners can still be verified. Entries can't be inserted
+/// before the entries index anymore, as they couldn't be verified.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Debug)]
+pub struct Checkpoint<A: Actor> {
+    /// Address of the Sequence.
+    pub address: Address,
+    /// Index of the entries the checkpoint was taken at.
+    pub entries_index: u64,
+    /// Identifier of the last entry before the entries index, if any.
+    pub entries_anchor: Option<Identifier<A>>,
+    /// Number of permissions folded into the checkpoint.
+    pub permissions_index: u64,
+    /// Number of owners folded into the checkpoint.
+    pub owners_index: u64,
+    /// Clock of the permissions operations accounted for in the checkpoint.
+    pub permissions: VClock<A>,
+    /// Clock of the owners operations accounted for in the checkpoint.
+    pub owners: VClock<A>,
+}
+
+/// Checkpoint signed by the owner(s) in effect at its entries index.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Debug)]
+pub struct SignedCheckpoint<A: Actor> {
+    /// The checkpoint.
+    pub checkpoint: Checkpoint<A>,
+    /// Signatures of the owner keys over the bincode serialisation of `checkpoint`.
+    pub signatures: BTreeMap<PublicKey, Signature>,
+}
+
+/// CRDT operations missing from a replica, as computed from its `SyncSummary`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Debug)]
+pub struct SyncDelta<A: Actor, P> {
+    /// Missing data operations.
+    pub data: Vec<Op<Entry, A>>,
+    /// Missing permissions operations.
+    pub permissions: Vec<Op<P, A>>,
+    /// Missing owners operations.
+    pub owners: Vec<Op<Owner, A>>,
 }
 
 impl<A, P> Display for SequenceCrdt<A, P>
@@ -49,7 +131,7 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "[")?;
-        for (i, entry) in self.data.iter().enumerate() {
+        for (i, entry) in self.entries().enumerate() {
             if i > 0 {
                 write!(f, ", ")?;
             }
@@ -71,6 +153,8 @@ where
             data: LSeq::new_with_args(actor.clone(), LSEQ_TREE_BASE, LSEQ_BOUNDARY),
             permissions: LSeq::new_with_args(actor.clone(), LSEQ_TREE_BASE, LSEQ_BOUNDARY),
             owners: LSeq::new_with_args(actor, LSEQ_TREE_BASE, LSEQ_BOUNDARY),
+            tombstones: BTreeMap::new(),
+            checkpoint: None,
         }
     }
 
@@ -84,73 +168,179 @@ where
         self.data.len() as u64
     }
 
-    /// Returns the last owners index.
+    /// Returns the last owners index, including the owners folded into the checkpoint.
     pub fn owners_index(&self) -> u64 {
-        self.owners.len() as u64
+        self.folded_owners() + self.owners.len() as u64
     }
 
-    /// Returns the last permissions index.
+    /// Returns the last permissions index, including the permissions folded into the checkpoint.
     pub fn permissions_index(&self) -> u64 {
-        self.permissions.len() as u64
+        self.folded_permissions() + self.permissions.len() as u64
+    }
+
+    /// Returns the identifier of the last entry, if any, which the owners and permissions
+    /// changed now are anchored to.
+    pub fn entries_anchor(&self) -> Option<&Identifier<A>> {
+        self.anchor_at(self.entries_index())
     }
 
     /// Append a new item to the SequenceCrdt.
     pub fn append(&mut self, entry: Entry) -> Op<Entry, A> {
         // We return the operation in case it needs to be broadcasted to other replicas
-        self.data.append(entry)
+        let op = self.data.append(entry);
+        self.data.apply(op.clone());
+        op
+    }
+
+    /// Insert a new item at `index` in the SequenceCrdt, shifting the following ones.
+    ///
+    /// Returns:
+    /// `Err::NoSuchEntry` if `index` is beyond the end of the data,
+    /// `Err::InvalidOperation` if `index` is before the entries index of the checkpoint.
+    pub fn insert_at(&mut self, index: Index, entry: Entry) -> Result<Op<Entry, A>> {
+        let i =
+            to_absolute_index(index, self.entries_index() as usize).ok_or(Error::NoSuchEntry)?;
+        if self.is_folded(self.anchor_at(i as u64)) {
+            return Err(Error::InvalidOperation);
+        }
+        let op = self.data.insert_index(i, entry);
+        self.data.apply(op.clone());
+        Ok(op)
+    }
+
+    /// Delete the item at `index` from the SequenceCrdt.
+    ///
+    /// Returns `None` if there is no entry at `index` or if it's already deleted.
+    pub fn delete(&mut self, index: Index) -> Option<Op<Entry, A>> {
+        let i = to_absolute_index(index, self.entries_index() as usize)?;
+        let entry = self.data.iter_entries().nth(i)?;
+        if self.tombstones.contains_key(&entry.id) {
+            return None;
+        }
+        let insert_op = Op::Insert {
+            id: entry.id.clone(),
+            dot: entry.dot.clone(),
+            val: entry.val.clone(),
+        };
+
+        // The entry is only marked as deleted, but the LSeq still needs to account for the dot
+        // of the deletion, so it's applied and the entry re-inserted right away.
+        let op = self.data.delete_index(i)?;
+        self.data.apply(op.clone());
+        self.data.apply(insert_op);
+
+        self.apply_crdt_op(op.clone());
+        Some(op)
     }
 
     /// Apply CRDT operation.
     pub fn apply_crdt_op(&mut self, op: Op<Entry, A>) {
-        self.data.apply(op)
+        match op {
+            Op::Insert { .. } => self.data.apply(op),
+            Op::Delete { id, remote, dot } => {
+                let tombstone = self.tombstones.entry(id).or_insert_with(|| Tombstone {
+                    remote,
+                    dots: Vec::new(),
+                });
+                if !tombstone.dots.contains(&dot) {
+                    tombstone.dots.push(dot);
+                }
+            }
+        }
     }
 
-    /// Gets the entry at `index` if it exists.
+    /// Gets the entry at `index` if it exists and it's not deleted.
     pub fn get(&self, index: Index) -> Option<&Entry> {
         let i = to_absolute_index(index, self.entries_index() as usize)?;
-        self.data.get(i)
+        self.data
+            .iter_entries()
+            .nth(i)
+            .filter(|entry| !self.tombstones.contains_key(&entry.id))
+            .map(|entry| &entry.val)
     }
 
-    /// Gets the last entry.
+    /// Gets the last entry which is not deleted.
     pub fn last_entry(&self) -> Option<&Entry> {
-        self.data.last()
+        self.entries().last()
+    }
+
+    /// Returns `true` if the entry at `index` has been deleted.
+    pub fn is_deleted(&self, index: Index) -> bool {
+        to_absolute_index(index, self.entries_index() as usize)
+            .and_then(|i| self.data.iter_entries().nth(i))
+            .is_some_and(|entry| self.tombstones.contains_key(&entry.id))
     }
 
-    /// Gets a complete list of permissions.
+    /// Gets a complete list of permissions, unless folded into the checkpoint.
     pub fn permissions(&self, index: impl Into<Index>) -> Option<&P> {
-        let index = to_absolute_index(index.into(), self.permissions.len())?;
-        self.permissions.get(index)
+        let index = to_absolute_index(index.into(), self.permissions_index() as usize)?;
+        self.permissions
+            .get(index.checked_sub(self.folded_permissions() as usize)?)
     }
 
-    /// Returns the owner's public key and the indices at the time it was added.
+    /// Returns the owner's public key and the indices at the time it was added, unless folded
+    /// into the checkpoint.
     pub fn owner(&self, owners_index: impl Into<Index>) -> Option<&Owner> {
-        let index = to_absolute_index(owners_index.into(), self.owners.len())?;
-        self.owners.get(index)
+        let index = to_absolute_index(owners_index.into(), self.owners_index() as usize)?;
+        self.owners
+            .get(index.checked_sub(self.folded_owners() as usize)?)
     }
 
-    /// Gets a list of keys and values with the given indices.
+    /// Gets a list of keys and values with the given indices. Deleted entries are skipped.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
     pub fn in_range(&self, start: Index, end: Index) -> Option<Entries> {
-        let start_index = to_absolute_index(start, self.entries_index() as usize)?;
-        let end_index = to_absolute_index(end, self.entries_index() as usize)?;
+        self.iter_range(start, end)
+            .map(|entries| entries.cloned().collect())
+    }
 
-        let range = self
-            .data
-            .iter()
-            .enumerate()
-            .filter_map(|(i, entry)| {
-                if i >= start_index && i < end_index {
-                    Some(entry.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Entries>();
+    /// Iterates over the entries with the given indices, without cloning them. Deleted entries
+    /// are skipped.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
+    pub fn iter_range(
+        &self,
+        start: Index,
+        end: Index,
+    ) -> Option<impl Iterator<Item = &Entry> + '_> {
+        self.indexed_range(start, end)
+            .map(|entries| entries.map(|(_, entry)| entry))
+    }
 
-        if range.is_empty() {
-            None
-        } else {
-            Some(range)
+    /// Gets a page of the entries with the given indices, holding at most `max_entries` entries
+    /// and `max_bytes` bytes of entries. Deleted entries are skipped.
+    ///
+    /// The page always holds at least one entry if the range isn't empty, even if it's bigger
+    /// than `max_bytes`, so that paging through the range always progresses.
+    ///
+    /// Returns `None` if any of the indices is out of bounds.
+    pub fn page(
+        &self,
+        start: Index,
+        end: Index,
+        max_entries: Option<u64>,
+        max_bytes: Option<u64>,
+    ) -> Option<Page> {
+        let mut entries = Entries::new();
+        let mut bytes = 0;
+        for (index, entry) in self.indexed_range(start, end)? {
+            let size = entry.len() as u64;
+            let is_full = max_entries.is_some_and(|max| entries.len() as u64 >= max)
+                || max_bytes.is_some_and(|max| bytes + size > max);
+            if is_full && !entries.is_empty() {
+                return Some(Page {
+                    entries,
+                    next: Some(Index::FromStart(index)),
+                });
+            }
+            bytes += size;
+            entries.push(entry.clone());
         }
+
+        Some(Page {
+            entries,
+            next: None,
+        })
     }
 
     /// Returns a tuple containing the last entries index, last owners index, and last permissions
@@ -168,29 +358,290 @@ where
     /// Adds a new permissions entry.
     /// The `Perm` struct should contain valid indices.
     pub fn append_permissions(&mut self, permissions: P) -> Op<P, A> {
-        self.permissions.append(permissions)
+        let op = self.permissions.append(permissions);
+        self.permissions.apply(op.clone());
+        op
     }
 
     /// Apply Permissions CRDT operation.
     pub fn apply_crdt_perms_op(&mut self, op: Op<P, A>) {
-        self.permissions.apply(op)
-    }
-
-    /// Adds a new owner entry.
-    pub fn append_owner(&mut self, public_key: PublicKey) -> Op<Owner, A> {
-        self.owners.append(Owner {
-            entries_index: self.entries_index(),
-            permissions_index: self.permissions_index(),
-            public_key,
-        })
+        let folded = self.checkpoint.as_ref().map(|c| &c.checkpoint.permissions);
+        apply_unless_folded(&mut self.permissions, folded, op)
     }
 
     /// Apply Owner CRDT operation.
     pub fn apply_crdt_owner_op(&mut self, op: Op<Owner, A>) {
-        self.owners.apply(op)
+        let folded = self.checkpoint.as_ref().map(|c| &c.checkpoint.owners);
+        apply_unless_folded(&mut self.owners, folded, op)
+    }
+
+    /// Returns the owner currently in effect, ignoring the stale owners.
+    pub fn current_owner(&self) -> Option<&Owner> {
+        self.valid_owners().last().map(|(_, owner)| owner)
+    }
+
+    /// Lists the concurrent owner transfers, keyed by the owners index they were made from.
+    ///
+    /// The owners of each fork are in the order of the history: the first one is in effect if it
+    /// isn't itself stale, and the others are stale.
+    pub fn owner_forks(&self) -> BTreeMap<u64, Vec<Owner>> {
+        let mut forks = BTreeMap::<u64, Vec<Owner>>::new();
+        for owner in self.owners.iter() {
+            forks
+                .entry(owner.owners_index)
+                .or_default()
+                .push(owner.clone());
+        }
+        forks.retain(|_, owners| owners.len() > 1);
+        forks
+    }
+
+    /// Applies a Permissions CRDT operation authored by `requester`, if the owner and
+    /// permissions in effect at the position of the new permissions allowed the requester to
+    /// manage permissions.
+    ///
+    /// Returns:
+    /// `Err::InvalidOperation` if the operation is not an insertion, as permissions are only
+    /// ever appended,
+    /// `Err::AccessDenied` if the operation is not allowed.
+    pub fn apply_checked_perms_op(&mut self, op: Op<P, A>, requester: PublicKey) -> Result<()> {
+        if let Op::Delete { .. } = op {
+            return Err(Error::InvalidOperation);
+        }
+        let index = self.folded_permissions() + position_of(&self.permissions, &op);
+        self.check_permissions_author(index, requester, self.entries_index())?;
+        self.apply_crdt_perms_op(op);
+        Ok(())
+    }
+
+    /// Applies an Owner CRDT operation authored by `requester`, if the requester was the owner
+    /// in effect at the position of the new owner, and the new owner was appended after all the
+    /// owners before that position.
+    ///
+    /// Returns:
+    /// `Err::InvalidOperation` if the operation is not an insertion, as owners are only ever
+    /// appended,
+    /// `Err::InvalidOwnersSuccessor` with the current owners index if the new owner is stale,
+    /// `Err::InvalidOwners` if there was no owner at that position,
+    /// `Err::AccessDenied` if the requester was not the owner.
+    pub fn apply_checked_owner_op(&mut self, op: Op<Owner, A>, requester: PublicKey) -> Result<()> {
+        let owners_index = match &op {
+            Op::Insert { val, .. } => val.owners_index,
+            Op::Delete { .. } => return Err(Error::InvalidOperation),
+        };
+        let index = self.folded_owners() + position_of(&self.owners, &op);
+        if owners_index != index {
+            return Err(Error::InvalidOwnersSuccessor(self.owners_index()));
+        }
+        self.check_owner_author(index, requester)?;
+        self.apply_crdt_owner_op(op);
+        Ok(())
+    }
+
+    /// Returns a summary of the operations held by this replica.
+    pub fn summary(&self) -> SyncSummary<A> {
+        let (folded_permissions, folded_owners) = match &self.checkpoint {
+            Some(signed) => (
+                signed.checkpoint.permissions.clone(),
+                signed.checkpoint.owners.clone(),
+            ),
+            None => (VClock::new(), VClock::new()),
+        };
+
+        SyncSummary {
+            data: contiguous_clock(
+                VClock::new(),
+                self.data
+                    .iter_entries()
+                    .map(|entry| &entry.dot)
+                    .chain(self.tombstones.values().flat_map(|t| t.dots.iter())),
+            ),
+            permissions: contiguous_clock(
+                folded_permissions,
+                self.permissions.iter_entries().map(|entry| &entry.dot),
+            ),
+            owners: contiguous_clock(
+                folded_owners,
+                self.owners.iter_entries().map(|entry| &entry.dot),
+            ),
+        }
+    }
+
+    /// Returns the operations held by this replica which are not accounted for in `summary`.
+    pub fn delta_since(&self, summary: &SyncSummary<A>) -> SyncDelta<A, P> {
+        SyncDelta {
+            data: missing_ops(&self.data, &summary.data)
+                .into_iter()
+                .chain(self.tombstones.iter().flat_map(|(id, tombstone)| {
+                    tombstone
+                        .dots
+                        .iter()
+                        .filter(move |dot| dot.counter > summary.data.get(&dot.actor))
+                        .map(move |dot| Op::Delete {
+                            id: id.clone(),
+                            remote: tombstone.remote.clone(),
+                            dot: dot.clone(),
+                        })
+                }))
+                .collect(),
+            permissions: missing_ops(&self.permissions, &summary.permissions),
+            owners: missing_ops(&self.owners, &summary.owners),
+        }
+    }
+
+    /// Applies all the operations of a delta.
+    pub fn apply_delta(&mut self, delta: SyncDelta<A, P>) {
+        delta.data.into_iter().for_each(|op| self.apply_crdt_op(op));
+        delta
+            .permissions
+            .into_iter()
+            .for_each(|op| self.apply_crdt_perms_op(op));
+        delta
+            .owners
+            .into_iter()
+            .for_each(|op| self.apply_crdt_owner_op(op));
+    }
+
+    /// Returns the checkpoint the permissions and owners history has been folded into, if any.
+    pub fn checkpoint(&self) -> Option<&SignedCheckpoint<A>> {
+        self.checkpoint.as_ref()
+    }
+
+    // Folds the permissions and owners history into `checkpoint`, which must have been verified.
+    fn fold_history(&mut self, checkpoint: SignedCheckpoint<A>) -> Result<()> {
+        let folded = &checkpoint.checkpoint;
+        let perms_to_fold = folded
+            .permissions_index
+            .checked_sub(self.folded_permissions())
+            .ok_or(Error::InvalidOperation)?;
+        let owners_to_fold = folded
+            .owners_index
+            .checked_sub(self.folded_owners())
+            .ok_or(Error::InvalidOperation)?;
+        let summary = self.summary();
+        if !(summary.permissions >= folded.permissions && summary.owners >= folded.owners) {
+            return Err(Error::InvalidOperation);
+        }
+
+        let perms_ops = folding_ops(&self.permissions, perms_to_fold, &folded.permissions)?;
+        let owners_ops = folding_ops(&self.owners, owners_to_fold, &folded.owners)?;
+        perms_ops
+            .into_iter()
+            .for_each(|op| self.permissions.apply(op));
+        owners_ops.into_iter().for_each(|op| self.owners.apply(op));
+        self.checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    // Returns the number of permissions folded into the checkpoint.
+    fn folded_permissions(&self) -> u64 {
+        self.checkpoint
+            .as_ref()
+            .map_or(0, |signed| signed.checkpoint.permissions_index)
+    }
+
+    // Returns the number of owners folded into the checkpoint.
+    fn folded_owners(&self) -> u64 {
+        self.checkpoint
+            .as_ref()
+            .map_or(0, |signed| signed.checkpoint.owners_index)
+    }
+
+    // Checks that `requester` could manage the permissions at `index` when the data had
+    // `entries_index` entries, as allowed by the owner and the permissions in effect before it.
+    fn check_permissions_author(
+        &self,
+        index: u64,
+        requester: PublicKey,
+        entries_index: u64,
+    ) -> Result<()> {
+        let owner = self
+            .valid_owners()
+            .map(|(_, owner)| owner)
+            .filter(|owner| owner.permissions_index <= index)
+            .last();
+        let permissions = index.checked_sub(1).and_then(|prev| self.permissions(prev));
+        check_permission(
+            owner,
+            permissions,
+            requester,
+            Action::ManagePermissions,
+            entries_index,
+        )
+    }
+
+    // Checks that `requester` could transfer the ownership at `index`, as the owner in effect
+    // before it.
+    fn check_owner_author(&self, index: u64, requester: PublicKey) -> Result<()> {
+        let prev_owner = self
+            .valid_owners()
+            .filter(|(prev, _)| *prev < index)
+            .last()
+            .map(|(_, owner)| owner)
+            .ok_or(Error::InvalidOwners)?;
+        if prev_owner.policy.is_satisfied_by(&requester) {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    // Iterates over the owners which are in effect, or were at some point, along with their
+    // index.
+    fn valid_owners(&self) -> impl Iterator<Item = (u64, &Owner)> + '_ {
+        let folded = self.folded_owners();
+        self.owners
+            .iter()
+            .zip(folded..)
+            .filter(|(owner, index)| owner.owners_index == *index)
+            .map(|(owner, index)| (index, owner))
+    }
+
+    // Returns the identifier of the entry before `entries_index`, if any, i.e. the anchor of
+    // the entries placed at `entries_index`.
+    fn anchor_at(&self, entries_index: u64) -> Option<&Identifier<A>> {
+        let index = (entries_index as usize).checked_sub(1)?;
+        self.data.iter_entries().nth(index).map(|entry| &entry.id)
     }
 
-    /// Checks if the requester is the last owner.
+    // Returns `true` if the entries placed after `anchor` are before the entries index of the
+    // checkpoint.
+    fn is_folded(&self, anchor: Option<&Identifier<A>>) -> bool {
+        self.checkpoint
+            .as_ref()
+            .is_some_and(|signed| anchor < signed.checkpoint.entries_anchor.as_ref())
+    }
+
+    // Iterates over the entries with the given indices which are not deleted, along with their
+    // absolute index.
+    fn indexed_range(
+        &self,
+        start: Index,
+        end: Index,
+    ) -> Option<impl Iterator<Item = (u64, &Entry)> + '_> {
+        let start_index = to_absolute_index(start, self.entries_index() as usize)?;
+        let end_index = to_absolute_index(end, self.entries_index() as usize)?;
+
+        Some(
+            self.data
+                .iter_entries()
+                .enumerate()
+                .skip(start_index)
+                .take(end_index.saturating_sub(start_index))
+                .filter(move |(_, entry)| !self.tombstones.contains_key(&entry.id))
+                .map(|(i, entry)| (i as u64, &entry.val)),
+        )
+    }
+
+    // Iterates over the entries which are not deleted.
+    fn entries(&self) -> impl Iterator<Item = &Entry> + '_ {
+        self.data
+            .iter_entries()
+            .filter(move |entry| !self.tombstones.contains_key(&entry.id))
+            .map(|entry| &entry.val)
+    }
+
+    /// Checks if the requester can act as the current owner(s) on its own.
     ///
     /// Returns:
     /// `Ok(())` if the requester is the owner,
@@ -198,16 +649,283 @@ where
     /// `Err::AccessDenied` if the requester is not the owner.
     pub fn check_is_last_owner(&self, requester: PublicKey) -> Result<()> {
         if self
-            .owner(Index::FromEnd(1))
+            .current_owner()
             .ok_or_else(|| Error::InvalidOwners)?
-            .public_key
-            == requester
+            .policy
+            .is_satisfied_by(&requester)
         {
             Ok(())
         } else {
             Err(Error::AccessDenied)
         }
     }
+
+    /// Checks if `signatures` of `message` are enough to act as the current owner(s), either as
+    /// a set of signatures of the owner keys or as a combined threshold signature.
+    ///
+    /// Returns:
+    /// `Ok(())` if enough owner keys signed,
+    /// `Err::InvalidOwners` if the last owner is invalid,
+    /// `Err::InvalidSignature` if the signature of an owner key is not valid,
+    /// `Err::AccessDenied` if not enough owner keys signed.
+    pub fn check_is_last_owner_with_signatures(
+        &self,
+        signatures: &BTreeMap<PublicKey, Signature>,
+        message: &[u8],
+    ) -> Result<()> {
+        self.current_owner()
+            .ok_or(Error::InvalidOwners)?
+            .policy
+            .verify(signatures, message)
+    }
+}
+
+impl<P> SequenceCrdt<PublicKey, P>
+where
+    P: Perm + Hash + Clone,
+{
+    /// Adds a new owner entry.
+    pub fn append_owner(&mut self, policy: OwnerPolicy) -> Op<Owner, PublicKey> {
+        let op = self.owners.append(Owner {
+            entries_index: self.entries_index(),
+            entries_anchor: self.entries_anchor().cloned(),
+            permissions_index: self.permissions_index(),
+            owners_index: self.owners_index(),
+            policy,
+        });
+        self.owners.apply(op.clone());
+        op
+    }
+
+    /// Applies a CRDT operation authored by `requester`, if allowed by the current owner and
+    /// permissions.
+    ///
+    /// An insertion must also be allowed by the owner and permissions in effect at its
+    /// position, which `check_history` checks it against later on. The current ones are checked
+    /// as well, so that revoked or expired permissions can't be used to insert entries before
+    /// their revocation.
+    ///
+    /// Returns:
+    /// `Err::InvalidOperation` if an insertion is before the entries index of the checkpoint,
+    /// `Err::AccessDenied` if the operation is not allowed,
+    /// `Err::PermissionExpired` if the requester's permissions are not in effect anymore.
+    pub fn apply_checked_op(
+        &mut self,
+        op: Op<Entry, PublicKey>,
+        requester: PublicKey,
+    ) -> Result<()> {
+        match op {
+            Op::Insert { .. } => {
+                let position = position_of(&self.data, &op);
+                let anchor = self.anchor_at(position);
+                if self.is_folded(anchor) {
+                    return Err(Error::InvalidOperation);
+                }
+                check_permission(
+                    self.owner_after(anchor),
+                    self.permissions_after(anchor),
+                    requester,
+                    Action::Append,
+                    position,
+                )?;
+                check_permission(
+                    self.current_owner(),
+                    self.permissions(Index::FromEnd(1)),
+                    requester,
+                    Action::Append,
+                    self.entries_index(),
+                )?;
+            }
+            Op::Delete { .. } => check_permission(
+                self.current_owner(),
+                self.permissions(Index::FromEnd(1)),
+                requester,
+                Action::Delete,
+                self.entries_index(),
+            )?,
+        }
+        self.apply_crdt_op(op);
+        Ok(())
+    }
+
+    /// Returns the owner in effect for the entry at `entries_index`, i.e. the last owner
+    /// anchored before it, or for the next entry appended if `entries_index` is the last entries
+    /// index. Returns `None` if the entry is before the checkpoint.
+    pub fn owner_at_entry(&self, entries_index: u64) -> Option<&Owner> {
+        let anchor = self.anchor_at(entries_index);
+        if entries_index > self.entries_index() || self.is_folded(anchor) {
+            return None;
+        }
+        self.owner_after(anchor)
+    }
+
+    /// Returns the permissions in effect for the entry at `entries_index`, i.e. the last ones
+    /// anchored before it, or for the next entry appended if `entries_index` is the last entries
+    /// index. Returns `None` if the entry is before the checkpoint.
+    pub fn permissions_at_entry(&self, entries_index: u64) -> Option<&P> {
+        let anchor = self.anchor_at(entries_index);
+        if entries_index > self.entries_index() || self.is_folded(anchor) {
+            return None;
+        }
+        self.permissions_after(anchor)
+    }
+
+    /// Takes a checkpoint of the permissions and owners history at `entries_index`, to be
+    /// signed by the owner in effect at that index.
+    ///
+    /// Returns `None` if `entries_index` is beyond the end of the data or before the current
+    /// checkpoint, or if this replica is missing permissions or owners operations.
+    pub fn take_checkpoint(&self, entries_index: u64) -> Option<Checkpoint<PublicKey>> {
+        let anchor = self.anchor_at(entries_index);
+        if entries_index > self.entries_index() || self.is_folded(anchor) {
+            return None;
+        }
+
+        let summary = self.summary();
+        let is_missing_ops = self
+            .permissions
+            .iter_entries()
+            .any(|entry| entry.dot.counter > summary.permissions.get(&entry.dot.actor))
+            || self
+                .owners
+                .iter_entries()
+                .any(|entry| entry.dot.counter > summary.owners.get(&entry.dot.actor));
+        if is_missing_ops {
+            return None;
+        }
+
+        // Everything before the permissions and owner in effect at `entries_index` is folded.
+        let permissions_index = self
+            .permissions
+            .iter()
+            .enumerate()
+            .filter(|(_, perms)| perms.entries_anchor() <= anchor)
+            .last()
+            .map_or(0, |(index, _)| index as u64);
+        let owners_index = self
+            .valid_owners()
+            .filter(|(_, owner)| owner.entries_anchor.as_ref() <= anchor)
+            .last()
+            .map_or(self.folded_owners(), |(index, _)| index);
+
+        Some(Checkpoint {
+            address: *self.address(),
+            entries_index,
+            entries_anchor: anchor.cloned(),
+            permissions_index: self.folded_permissions() + permissions_index,
+            owners_index,
+            permissions: summary.permissions,
+            owners: summary.owners,
+        })
+    }
+
+    /// Replays the history of the data, checking each entry against the owner and permissions
+    /// which were in effect when it was appended by its author. Each permissions and owner
+    /// change is checked as well against the owner and permissions in effect before it, unless
+    /// those were folded into the checkpoint, or it's the first owner.
+    ///
+    /// Returns:
+    /// `Ok(())` if all the entries, permissions and owners were authorised,
+    /// `Err::UnauthorisedEntries` with the index and author of every one which was not.
+    pub fn check_history(&self) -> Result<()> {
+        let mut unauthorised = BTreeMap::new();
+        let mut anchor = None;
+        for (index, entry) in self.data.iter_entries().enumerate() {
+            let author = entry.dot.actor;
+            if !self.is_folded(anchor)
+                && check_permission(
+                    self.owner_after(anchor),
+                    self.permissions_after(anchor),
+                    author,
+                    Action::Append,
+                    index as u64,
+                )
+                .is_err()
+            {
+                let _ = unauthorised.insert(HistoryIndex::Entry(index as u64), author);
+            }
+            anchor = Some(&entry.id);
+        }
+
+        let folded = self.folded_permissions();
+        for (index, entry) in (folded..).zip(self.permissions.iter_entries()) {
+            let author = entry.dot.actor;
+            let is_first_kept = self.checkpoint.is_some() && index == folded;
+            if !is_first_kept
+                && self
+                    .check_permissions_author(index, author, entry.val.entries_index())
+                    .is_err()
+            {
+                let _ = unauthorised.insert(HistoryIndex::Permissions(index), author);
+            }
+        }
+
+        for (index, entry) in (self.folded_owners()..).zip(self.owners.iter_entries()) {
+            let author = entry.dot.actor;
+            if let Err(Error::AccessDenied) = self.check_owner_author(index, author) {
+                let _ = unauthorised.insert(HistoryIndex::Owner(index), author);
+            }
+        }
+
+        if unauthorised.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnauthorisedEntries(unauthorised))
+        }
+    }
+
+    /// Folds the permissions and owners history into `checkpoint`, discarding the permissions
+    /// and owners superseded before its entries index. Checkpoints older than the current one
+    /// are ignored.
+    ///
+    /// Returns:
+    /// `Ok(())` if the history was folded,
+    /// `Err::InvalidOperation` if the checkpoint is not for this data, or doesn't match the
+    /// history held by this replica,
+    /// `Err::InvalidOwners` if there is no owner at the entries index of the checkpoint,
+    /// `Err::InvalidSignature` if the signature of an owner key is not valid,
+    /// `Err::AccessDenied` if not enough owner keys signed the checkpoint.
+    pub fn compact(&mut self, checkpoint: SignedCheckpoint<PublicKey>) -> Result<()> {
+        if checkpoint.checkpoint.address != *self.address() {
+            return Err(Error::InvalidOperation);
+        }
+        let anchor = checkpoint.checkpoint.entries_anchor.as_ref();
+        if self
+            .checkpoint
+            .as_ref()
+            .is_some_and(|signed| anchor <= signed.checkpoint.entries_anchor.as_ref())
+        {
+            return Ok(());
+        }
+        if checkpoint.checkpoint.entries_index > self.entries_index()
+            || self.anchor_at(checkpoint.checkpoint.entries_index) != anchor
+        {
+            return Err(Error::InvalidOperation);
+        }
+
+        let owner = self.owner_after(anchor).ok_or(Error::InvalidOwners)?;
+        owner.policy.verify(
+            &checkpoint.signatures,
+            utils::serialise(&checkpoint.checkpoint),
+        )?;
+        self.fold_history(checkpoint)
+    }
+
+    // Returns the owner in effect for the entries placed after `anchor`.
+    fn owner_after(&self, anchor: Option<&EntryId>) -> Option<&Owner> {
+        self.valid_owners()
+            .map(|(_, owner)| owner)
+            .filter(|owner| owner.entries_anchor.as_ref() <= anchor)
+            .last()
+    }
+
+    // Returns the permissions in effect for the entries placed after `anchor`.
+    fn permissions_after(&self, anchor: Option<&EntryId>) -> Option<&P> {
+        self.permissions
+            .iter()
+            .filter(|perms| perms.entries_anchor() <= anchor)
+            .last()
+    }
 }
 
 // Private helpers
@@ -219,3 +937,107 @@ fn to_absolute_index(index: Index, count: usize) -> Option<usize> {
         Index::FromEnd(index) => count.checked_sub(index as usize),
     }
 }
+
+// Returns the index the item of `op` has, or would have once inserted, in `lseq`.
+fn position_of<T: Clone, A: Actor>(lseq: &LSeq<T, A>, op: &Op<T, A>) -> u64 {
+    lseq.iter_entries()
+        .take_while(|entry| entry.id < *op.id())
+        .count() as u64
+}
+
+// Checks permissions for given `action` against the provided owner and permissions.
+fn check_permission<P: Perm>(
+    owner: Option<&Owner>,
+    permissions: Option<&P>,
+    requester: PublicKey,
+    action: Action,
+    entries_index: u64,
+) -> Result<()> {
+    match owner {
+        Some(owner) if owner.policy.is_satisfied_by(&requester) => Ok(()),
+        _ => permissions.ok_or(Error::AccessDenied)?.is_action_allowed(
+            requester,
+            action,
+            entries_index,
+        ),
+    }
+}
+
+// Builds a clock which, for each actor, only accounts for the dots held without any gap after
+// the ones accounted for in `base`.
+fn contiguous_clock<'a, A: Actor + 'a>(
+    base: VClock<A>,
+    dots: impl Iterator<Item = &'a Dot<A>>,
+) -> VClock<A> {
+    let mut counters = BTreeMap::<A, BTreeSet<u64>>::new();
+    for dot in dots {
+        let _ = counters
+            .entry(dot.actor.clone())
+            .or_default()
+            .insert(dot.counter);
+    }
+
+    let mut clock = base;
+    for (actor, counters) in counters {
+        let mut held = clock.get(&actor);
+        while counters.contains(&(held + 1)) {
+            held += 1;
+        }
+        clock.apply(Dot::new(actor, held));
+    }
+    clock
+}
+
+// Applies `op`, unless it inserts an item already folded into a checkpoint with the `folded`
+// clock.
+fn apply_unless_folded<T: Clone, A: Actor>(
+    lseq: &mut LSeq<T, A>,
+    folded: Option<&VClock<A>>,
+    op: Op<T, A>,
+) {
+    let is_folded = match (&op, folded) {
+        (Op::Insert { dot, .. }, Some(clock)) => dot.counter <= clock.get(&dot.actor),
+        _ => false,
+    };
+    if !is_folded {
+        lseq.apply(op)
+    }
+}
+
+// Returns the operations removing the first `count` items of `lseq`, which must all be accounted
+// for in the `folded` clock. The operations reuse the dots of the items, so they don't advance
+// the clock of the LSeq.
+fn folding_ops<T: Clone, A: Actor>(
+    lseq: &LSeq<T, A>,
+    count: u64,
+    folded: &VClock<A>,
+) -> Result<Vec<Op<T, A>>> {
+    if count > 0 && lseq.len() as u64 <= count {
+        return Err(Error::InvalidOperation);
+    }
+    lseq.iter_entries()
+        .take(count as usize)
+        .map(|entry| {
+            if entry.dot.counter > folded.get(&entry.dot.actor) {
+                return Err(Error::InvalidOperation);
+            }
+            Ok(Op::Delete {
+                id: entry.id.clone(),
+                remote: entry.dot.clone(),
+                dot: entry.dot.clone(),
+            })
+        })
+        .collect()
+}
+
+// Returns the insert operations of the entries whose dots are not covered by `clock`.
+fn missing_ops<T: Clone, A: Actor>(lseq: &LSeq<T, A>, clock: &VClock<A>) -> Vec<Op<T, A>> {
+    lseq.iter_entries()
+        .filter(|entry| entry.dot.counter > clock.get(&entry.dot.actor))
+        .map(|entry| Op::Insert {
+            id: entry.id.clone(),
+            dot: entry.dot.clone(),
+            val: entry.val.clone(),
+        })
+        .collect()
+}
@@ -28,6 +28,11 @@ pub(crate) fn serialise<T: Serialize>(data: &T) -> Vec<u8> {
     unwrap!(bincode::serialize(data))
 }
 
+/// Wrapper for raw bincode::deserialize, surfacing errors as `Error::FailedToParse`.
+pub(crate) fn deserialise<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    bincode::deserialize(bytes).map_err(|e| Error::FailedToParse(e.to_string()))
+}
+
 /// Wrapper for z-Base-32 multibase::encode.
 pub(crate) fn encode<T: Serialize>(data: &T) -> String {
     let serialised = serialise(&data);